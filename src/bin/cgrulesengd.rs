@@ -0,0 +1,65 @@
+//!
+//! cgrulesengd [-f <rules file>] [-s <socket path>] [-v]
+//!
+//! Classify processes with cgrules.conf, logs go to stderr.
+//!
+
+use libcgroup_rs::initialization::CGroupInitializer;
+use libcgroup_rs::rulesengd::*;
+
+
+struct StderrLogger;
+
+impl log::Log for StderrLogger{
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("[{}] {}",record.level(),record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER:StderrLogger = StderrLogger;
+
+
+fn usage()->!{
+    eprintln!("Usage: cgrulesengd [-f <rules file>] [-s <socket path>] [-v|-vv]");
+    std::process::exit(2);
+}
+
+
+fn main()->Result<(),Box<dyn std::error::Error>>{
+    let mut rules_file:Option<String> = None;
+    let mut socket_path = String::from(CGRULE_CGRED_SOCKET_PATH);
+    let mut level = log::LevelFilter::Warn;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-f" => rules_file = Some(args.next().unwrap_or_else(|| usage())),
+            "-s" => socket_path = args.next().unwrap_or_else(|| usage()),
+            "-v" => level = log::LevelFilter::Info,
+            "-vv" => level = log::LevelFilter::Debug,
+            _ => usage(),
+        }
+    }
+    log::set_logger(&LOGGER).map_err(|e| format!("{}",e))?;
+    log::set_max_level(level);
+
+    CGroupInitializer::init()?;
+    let source = match rules_file {
+        Some(path) => FileRuleSource::new(path),
+        None => FileRuleSource::system(),
+    };
+    let mut engine = RulesEngine::new(source)?;
+    engine.listen_unchanged(&socket_path)?;
+
+    let connector = ProcConnector::new()?;
+    engine.run(&connector)?;
+    Ok(())
+}
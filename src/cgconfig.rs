@@ -0,0 +1,660 @@
+//!
+//! ### Configuration file(cgconfig.conf)
+//!
+//! URL: [cgconfig.conf](https://man7.org/linux/man-pages/man5/cgconfig.conf.5.html)
+//!
+//! Blocks:
+//!    - mount
+//!    - group
+//!    - template
+//!    - default
+//!    - namespace
+//!
+//! Usage:
+//! ```
+//! use libcgroup_rs::cgconfig::CGConfig;
+//!
+//! fn main()->Result<(),Box<dyn std::error::Error>>{
+//!     let config = CGConfig::parse(r#"
+//!         group daemons/www {
+//!             perm {
+//!                 task { uid = root; gid = webmaster; fperm = 770; }
+//!                 admin { uid = root; gid = root; }
+//!             }
+//!             cpu { cpu.shares = "1000"; }
+//!         }
+//!     "#)?;
+//!
+//!     for group in config.groups() {
+//!         println!("Group = {}",group.name);
+//!     }
+//!
+//!     // pretty print
+//!     println!("{}",config);
+//!     Ok(())
+//! }
+//! ```
+//!
+
+
+/// Position in the source, line and column start at 1
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+pub struct Span{
+    pub line:usize,
+    pub column:usize,
+}
+
+impl std::fmt::Display for Span{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f,"{}:{}",self.line,self.column)
+    }
+}
+
+
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct ConfigParseError{
+    pub span:Span,
+    pub message:String,
+}
+
+impl ConfigParseError{
+    fn new(span:Span,message:String)->Self{
+        Self{ span, message }
+    }
+}
+
+impl std::fmt::Display for ConfigParseError{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f,"{}: {}",self.span,self.message)
+    }
+}
+
+impl std::error::Error for ConfigParseError {}
+
+impl From<ConfigParseError> for std::io::Error{
+    fn from(e: ConfigParseError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData,e.to_string())
+    }
+}
+
+
+
+/// `key = value;`
+#[derive(Debug,Clone,PartialEq,Eq,Default)]
+pub struct Assignment{
+    pub key:String,
+    pub value:String,
+    pub span:Span,
+}
+
+impl Assignment{
+    pub fn new(key:&str,value:&str)->Self{
+        Self{ key:key.to_string(), value:value.to_string(), span:Span::default() }
+    }
+}
+
+
+/// `mount { cpu = /sys/fs/cgroup/cpu; }`
+#[derive(Debug,Clone,PartialEq,Eq,Default)]
+pub struct MountBlock{
+    pub entries:Vec<Assignment>,
+    pub span:Span,
+}
+
+
+/// `task { uid = root; gid = root; fperm = 660; }`
+#[derive(Debug,Clone,PartialEq,Eq,Default)]
+pub struct TaskPerm{
+    pub uid:Option<String>,
+    pub gid:Option<String>,
+    pub fperm:Option<String>,
+    pub span:Span,
+}
+
+
+/// `admin { uid = root; gid = root; dperm = 775; fperm = 664; }`
+#[derive(Debug,Clone,PartialEq,Eq,Default)]
+pub struct AdminPerm{
+    pub uid:Option<String>,
+    pub gid:Option<String>,
+    pub dperm:Option<String>,
+    pub fperm:Option<String>,
+    pub span:Span,
+}
+
+
+#[derive(Debug,Clone,PartialEq,Eq,Default)]
+pub struct PermBlock{
+    pub task:Option<TaskPerm>,
+    pub admin:Option<AdminPerm>,
+    pub span:Span,
+}
+
+
+/// `cpu { cpu.shares = 1000; }`
+#[derive(Debug,Clone,PartialEq,Eq,Default)]
+pub struct ControllerBlock{
+    pub name:String,
+    pub params:Vec<Assignment>,
+    pub span:Span,
+}
+
+
+/// Body of `group` and `template`
+#[derive(Debug,Clone,PartialEq,Eq,Default)]
+pub struct GroupBlock{
+    pub name:String,
+    pub perm:Option<PermBlock>,
+    pub controllers:Vec<ControllerBlock>,
+    pub span:Span,
+}
+
+impl GroupBlock{
+    pub fn get_controller(&self,ctrl_name:&str)->Option<&ControllerBlock>{
+        self.controllers.iter().find(|c| c.name == ctrl_name)
+    }
+}
+
+
+/// `default { perm { ... } }`
+#[derive(Debug,Clone,PartialEq,Eq,Default)]
+pub struct DefaultBlock{
+    pub perm:PermBlock,
+    pub span:Span,
+}
+
+
+/// `namespace { cpu = daemons; }`
+#[derive(Debug,Clone,PartialEq,Eq,Default)]
+pub struct NamespaceBlock{
+    pub entries:Vec<Assignment>,
+    pub span:Span,
+}
+
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub enum ConfigItem{
+    Mount(MountBlock),
+    Group(GroupBlock),
+    Template(GroupBlock),
+    Default(DefaultBlock),
+    Namespace(NamespaceBlock),
+}
+
+
+/// Parsed cgconfig.conf, items keep the order of the source
+#[derive(Debug,Clone,PartialEq,Eq,Default)]
+pub struct CGConfig{
+    pub items:Vec<ConfigItem>,
+}
+
+impl CGConfig{
+
+    pub fn from_file(pathname:&str)->Result<Self,std::io::Error>{
+        Ok(Self::parse(&std::fs::read_to_string(pathname)?)?)
+    }
+
+    pub fn parse(content:&str)->Result<Self,ConfigParseError>{
+        Parser::new(Lexer::new(content).tokenize()?).parse_config()
+    }
+
+    /// Values containing `"` or `\` are refused, libcgroup's lexer has no escapes for them
+    pub fn write_file(&self,pathname:&str)->Result<(),std::io::Error>{
+        if let Some(value) = self.strings().into_iter().find(|v| v.contains(['"','\\'])) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Value can not be written to a config = {}",value)
+            ));
+        }
+        std::fs::write(pathname,self.to_string())
+    }
+
+    /// Every name, key and value printed by `Display`
+    fn strings(&self)->Vec<&str>{
+        fn perm_strings<'a>(perm:&'a PermBlock,strings:&mut Vec<&'a str>){
+            if let Some(task) = &perm.task {
+                strings.extend([&task.uid,&task.gid,&task.fperm].iter().filter_map(|v| v.as_deref()));
+            }
+            if let Some(admin) = &perm.admin {
+                strings.extend([&admin.uid,&admin.gid,&admin.dperm,&admin.fperm].iter().filter_map(|v| v.as_deref()));
+            }
+        }
+
+        let mut strings = Vec::new();
+        for item in self.items.iter() {
+            match item {
+                ConfigItem::Mount(MountBlock{ entries, .. }) | ConfigItem::Namespace(NamespaceBlock{ entries, .. }) => {
+                    strings.extend(entries.iter().flat_map(|a| [a.key.as_str(),a.value.as_str()]));
+                }
+                ConfigItem::Group(g) | ConfigItem::Template(g) => {
+                    strings.push(g.name.as_str());
+                    if let Some(perm) = &g.perm {
+                        perm_strings(perm,&mut strings);
+                    }
+                    for ctrl in g.controllers.iter() {
+                        strings.push(ctrl.name.as_str());
+                        strings.extend(ctrl.params.iter().flat_map(|a| [a.key.as_str(),a.value.as_str()]));
+                    }
+                }
+                ConfigItem::Default(d) => perm_strings(&d.perm,&mut strings),
+            }
+        }
+        strings
+    }
+
+    pub fn mounts(&self)->impl Iterator<Item=&MountBlock>{
+        self.items.iter().filter_map(|item| match item {
+            ConfigItem::Mount(m) => Some(m),
+            _ => None
+        })
+    }
+
+    pub fn groups(&self)->impl Iterator<Item=&GroupBlock>{
+        self.items.iter().filter_map(|item| match item {
+            ConfigItem::Group(g) => Some(g),
+            _ => None
+        })
+    }
+
+    pub fn templates(&self)->impl Iterator<Item=&GroupBlock>{
+        self.items.iter().filter_map(|item| match item {
+            ConfigItem::Template(t) => Some(t),
+            _ => None
+        })
+    }
+
+    pub fn default_perm(&self)->Option<&DefaultBlock>{
+        self.items.iter().rev().find_map(|item| match item {
+            ConfigItem::Default(d) => Some(d),
+            _ => None
+        })
+    }
+
+    pub fn get_group(&self,name:&str)->Option<&GroupBlock>{
+        self.groups().find(|g| g.name == name)
+    }
+}
+
+
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+enum TokenKind{
+    Word(String),
+    Quoted(String),
+    LBrace,
+    RBrace,
+    Eq,
+    Semi,
+}
+
+#[derive(Debug,Clone)]
+struct Token{
+    kind:TokenKind,
+    span:Span,
+}
+
+impl Token{
+    fn describe(&self)->String{
+        match &self.kind {
+            TokenKind::Word(w) => format!("'{}'",w),
+            TokenKind::Quoted(q) => format!("\"{}\"",q),
+            TokenKind::LBrace => String::from("'{'"),
+            TokenKind::RBrace => String::from("'}'"),
+            TokenKind::Eq => String::from("'='"),
+            TokenKind::Semi => String::from("';'"),
+        }
+    }
+}
+
+
+struct Lexer<'a>{
+    chars:std::iter::Peekable<std::str::Chars<'a>>,
+    line:usize,
+    column:usize,
+}
+
+impl<'a> Lexer<'a>{
+    fn new(content:&'a str)->Self{
+        Self{ chars:content.chars().peekable(), line:1, column:1 }
+    }
+
+    fn span(&self)->Span{
+        Span{ line:self.line, column:self.column }
+    }
+
+    fn bump(&mut self)->Option<char>{
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn is_word_char(c:char)->bool{
+        !c.is_whitespace() && !matches!(c,'{' | '}' | '=' | ';' | '"' | '#')
+    }
+
+    fn tokenize(mut self)->Result<Vec<Token>,ConfigParseError>{
+        let mut tokens = Vec::new();
+        while let Some(&c) = self.chars.peek() {
+            let span = self.span();
+            let kind = match c {
+                c if c.is_whitespace() => {
+                    self.bump();
+                    continue;
+                }
+                '#' => {
+                    while let Some(&c) = self.chars.peek() {
+                        if c == '\n' {
+                            break;
+                        }
+                        self.bump();
+                    }
+                    continue;
+                }
+                '{' => { self.bump(); TokenKind::LBrace }
+                '}' => { self.bump(); TokenKind::RBrace }
+                '=' => { self.bump(); TokenKind::Eq }
+                ';' => { self.bump(); TokenKind::Semi }
+                '"' => {
+                    self.bump();
+                    let mut value = String::new();
+                    loop {
+                        match self.bump() {
+                            // no escapes in libcgroup's lexer, a backslash is kept as is
+                            Some('"') => break,
+                            Some(c) => value.push(c),
+                            None => return Err(ConfigParseError::new(span,String::from("Unterminated string"))),
+                        }
+                    }
+                    TokenKind::Quoted(value)
+                }
+                _ => {
+                    let mut word = String::new();
+                    while let Some(&c) = self.chars.peek() {
+                        if !Self::is_word_char(c) {
+                            break;
+                        }
+                        word.push(c);
+                        self.bump();
+                    }
+                    TokenKind::Word(word)
+                }
+            };
+            tokens.push(Token{ kind, span });
+        }
+        Ok(tokens)
+    }
+}
+
+
+struct Parser{
+    tokens:Vec<Token>,
+    pos:usize,
+    eof:Span,
+}
+
+impl Parser{
+    fn new(tokens:Vec<Token>)->Self{
+        let eof = tokens.last().map(|t| t.span).unwrap_or(Span{ line:1, column:1 });
+        Self{ tokens, pos:0, eof }
+    }
+
+    fn peek(&self)->Option<&Token>{
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self)->Result<Token,ConfigParseError>{
+        match self.tokens.get(self.pos) {
+            Some(t) => {
+                self.pos += 1;
+                Ok(t.clone())
+            }
+            None => Err(ConfigParseError::new(self.eof,String::from("Unexpected end of file"))),
+        }
+    }
+
+    fn expect(&mut self,kind:TokenKind)->Result<Token,ConfigParseError>{
+        let t = self.next()?;
+        if t.kind != kind {
+            let expected = Token{ kind, span:t.span }.describe();
+            return Err(ConfigParseError::new(
+                t.span,
+                format!("Expected {}, found {}",expected,t.describe())
+            ));
+        }
+        Ok(t)
+    }
+
+    /// Word or quoted string
+    fn value(&mut self,what:&str)->Result<(String,Span),ConfigParseError>{
+        let t = self.next()?;
+        match t.kind {
+            TokenKind::Word(w) => Ok((w,t.span)),
+            TokenKind::Quoted(q) => Ok((q,t.span)),
+            _ => Err(ConfigParseError::new(t.span,format!("Expected {}, found {}",what,t.describe()))),
+        }
+    }
+
+    fn is_next(&self,kind:&TokenKind)->bool{
+        matches!(self.peek(),Some(t) if &t.kind == kind)
+    }
+
+    fn parse_config(&mut self)->Result<CGConfig,ConfigParseError>{
+        let mut config = CGConfig::default();
+        while self.peek().is_some() {
+            let (keyword,span) = self.value("block")?;
+            let item = match keyword.as_str() {
+                "mount" => {
+                    let entries = self.parse_assignments()?;
+                    ConfigItem::Mount(MountBlock{ entries, span })
+                }
+                "namespace" => {
+                    let entries = self.parse_assignments()?;
+                    ConfigItem::Namespace(NamespaceBlock{ entries, span })
+                }
+                "group" => ConfigItem::Group(self.parse_group(span)?),
+                "template" => ConfigItem::Template(self.parse_group(span)?),
+                "default" => {
+                    self.expect(TokenKind::LBrace)?;
+                    let (perm_keyword,perm_span) = self.value("'perm'")?;
+                    if perm_keyword != "perm" {
+                        return Err(ConfigParseError::new(
+                            perm_span,
+                            format!("Expected 'perm' in default, found '{}'",perm_keyword)
+                        ));
+                    }
+                    let perm = self.parse_perm(perm_span)?;
+                    self.expect(TokenKind::RBrace)?;
+                    ConfigItem::Default(DefaultBlock{ perm, span })
+                }
+                _ => return Err(ConfigParseError::new(span,format!("Unknown block '{}'",keyword))),
+            };
+            config.items.push(item);
+        }
+        Ok(config)
+    }
+
+    /// `{ key = value; ... }`
+    fn parse_assignments(&mut self)->Result<Vec<Assignment>,ConfigParseError>{
+        self.expect(TokenKind::LBrace)?;
+        let mut entries = Vec::new();
+        while !self.is_next(&TokenKind::RBrace) {
+            let (key,span) = self.value("name")?;
+            self.expect(TokenKind::Eq)?;
+            let (value,_) = self.value("value")?;
+            self.expect(TokenKind::Semi)?;
+            entries.push(Assignment{ key, value, span });
+        }
+        self.expect(TokenKind::RBrace)?;
+        Ok(entries)
+    }
+
+    fn parse_group(&mut self,span:Span)->Result<GroupBlock,ConfigParseError>{
+        let (name,_) = self.value("group name")?;
+        self.expect(TokenKind::LBrace)?;
+        let mut group = GroupBlock{ name, span, ..GroupBlock::default() };
+        while !self.is_next(&TokenKind::RBrace) {
+            let (name,span) = self.value("controller or 'perm'")?;
+            if name == "perm" {
+                if group.perm.is_some() {
+                    return Err(ConfigParseError::new(span,String::from("Duplicate perm block")));
+                }
+                group.perm = Some(self.parse_perm(span)?);
+            } else {
+                if group.get_controller(&name).is_some() {
+                    return Err(ConfigParseError::new(span,format!("Duplicate controller '{}'",name)));
+                }
+                let params = self.parse_assignments()?;
+                group.controllers.push(ControllerBlock{ name, params, span });
+            }
+        }
+        self.expect(TokenKind::RBrace)?;
+        Ok(group)
+    }
+
+    fn parse_perm(&mut self,span:Span)->Result<PermBlock,ConfigParseError>{
+        self.expect(TokenKind::LBrace)?;
+        let mut perm = PermBlock{ span, ..PermBlock::default() };
+        while !self.is_next(&TokenKind::RBrace) {
+            let (name,span) = self.value("'task' or 'admin'")?;
+            let entries = self.parse_assignments()?;
+            match name.as_str() {
+                "task" if perm.task.is_none() => {
+                    let mut task = TaskPerm{ span, ..TaskPerm::default() };
+                    for a in entries {
+                        let slot = match a.key.as_str() {
+                            "uid" => &mut task.uid,
+                            "gid" => &mut task.gid,
+                            "fperm" => &mut task.fperm,
+                            _ => return Err(Self::unknown_perm(&a,"task")),
+                        };
+                        *slot = Some(Self::perm_value(a)?);
+                    }
+                    perm.task = Some(task);
+                }
+                "admin" if perm.admin.is_none() => {
+                    let mut admin = AdminPerm{ span, ..AdminPerm::default() };
+                    for a in entries {
+                        let slot = match a.key.as_str() {
+                            "uid" => &mut admin.uid,
+                            "gid" => &mut admin.gid,
+                            "dperm" => &mut admin.dperm,
+                            "fperm" => &mut admin.fperm,
+                            _ => return Err(Self::unknown_perm(&a,"admin")),
+                        };
+                        *slot = Some(Self::perm_value(a)?);
+                    }
+                    perm.admin = Some(admin);
+                }
+                "task" | "admin" => {
+                    return Err(ConfigParseError::new(span,format!("Duplicate {} block",name)));
+                }
+                _ => return Err(ConfigParseError::new(span,format!("Unknown perm block '{}'",name))),
+            }
+        }
+        self.expect(TokenKind::RBrace)?;
+        Ok(perm)
+    }
+
+    fn unknown_perm(a:&Assignment,block:&str)->ConfigParseError{
+        ConfigParseError::new(a.span,format!("Unknown {} permission '{}'",block,a.key))
+    }
+
+    /// Modes are octal numbers, owners are names or ids
+    fn perm_value(a:Assignment)->Result<String,ConfigParseError>{
+        if a.key.ends_with("perm") && u32::from_str_radix(&a.value,8).map_or(true,|m| m > 0o7777) {
+            return Err(ConfigParseError::new(a.span,format!("Invalid mode {} = {}",a.key,a.value)));
+        }
+        Ok(a.value)
+    }
+}
+
+
+
+/// Quote values which would not survive as a single word, see `CGConfig::write_file` for `"` and `\`
+fn quote(value:&str)->String{
+    if !value.is_empty() && value.chars().all(Lexer::is_word_char) {
+        return value.to_string();
+    }
+    format!("\"{}\"",value)
+}
+
+fn write_assignments(f:&mut std::fmt::Formatter<'_>,indent:&str,entries:&[Assignment])->std::fmt::Result{
+    for a in entries {
+        writeln!(f,"{}{} = {};",indent,quote(&a.key),quote(&a.value))?;
+    }
+    Ok(())
+}
+
+fn write_perm(f:&mut std::fmt::Formatter<'_>,indent:&str,perm:&PermBlock)->std::fmt::Result{
+    writeln!(f,"{}perm {{",indent)?;
+    let inner = format!("{}\t",indent);
+    let field = format!("{}\t\t",indent);
+    if let Some(task) = &perm.task {
+        writeln!(f,"{}task {{",inner)?;
+        for (key,value) in [("uid",&task.uid),("gid",&task.gid),("fperm",&task.fperm)] {
+            if let Some(v) = value {
+                writeln!(f,"{}{} = {};",field,key,quote(v))?;
+            }
+        }
+        writeln!(f,"{}}}",inner)?;
+    }
+    if let Some(admin) = &perm.admin {
+        writeln!(f,"{}admin {{",inner)?;
+        for (key,value) in [("uid",&admin.uid),("gid",&admin.gid),("dperm",&admin.dperm),("fperm",&admin.fperm)] {
+            if let Some(v) = value {
+                writeln!(f,"{}{} = {};",field,key,quote(v))?;
+            }
+        }
+        writeln!(f,"{}}}",inner)?;
+    }
+    writeln!(f,"{}}}",indent)
+}
+
+fn write_group(f:&mut std::fmt::Formatter<'_>,keyword:&str,group:&GroupBlock)->std::fmt::Result{
+    writeln!(f,"{} {} {{",keyword,quote(&group.name))?;
+    if let Some(perm) = &group.perm {
+        write_perm(f,"\t",perm)?;
+    }
+    for ctrl in group.controllers.iter() {
+        writeln!(f,"\t{} {{",quote(&ctrl.name))?;
+        write_assignments(f,"\t\t",&ctrl.params)?;
+        writeln!(f,"\t}}")?;
+    }
+    writeln!(f,"}}")
+}
+
+impl std::fmt::Display for CGConfig{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (idx,item) in self.items.iter().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            match item {
+                ConfigItem::Mount(m) => {
+                    writeln!(f,"mount {{")?;
+                    write_assignments(f,"\t",&m.entries)?;
+                    writeln!(f,"}}")?;
+                }
+                ConfigItem::Namespace(n) => {
+                    writeln!(f,"namespace {{")?;
+                    write_assignments(f,"\t",&n.entries)?;
+                    writeln!(f,"}}")?;
+                }
+                ConfigItem::Group(g) => write_group(f,"group",g)?,
+                ConfigItem::Template(t) => write_group(f,"template",t)?,
+                ConfigItem::Default(d) => {
+                    writeln!(f,"default {{")?;
+                    write_perm(f,"\t",&d.perm)?;
+                    writeln!(f,"}}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
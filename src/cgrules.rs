@@ -0,0 +1,507 @@
+//!
+//! ### Rules file(cgrules.conf)
+//!
+//! URL: [cgrules.conf](https://man7.org/linux/man-pages/man5/cgrules.conf.5.html)
+//!
+//! Line format:
+//!    - `<user>[:<process name>] <controllers> <destination> [<options>]`
+//!    - user is a name, `@group`, `*` for everybody or `%` to continue the previous rule
+//!    - controllers are separated by comma, `*` for every controller
+//!    - destination may use the %u/%U/%g/%G/%p/%P substitutions of templates
+//!    - options are separated by comma, `ignore` or `ignore_rt`
+//!
+//! Functions:
+//!    - cgroup_init_rules_cache
+//!    - cgroup_reload_cached_rules
+//!    - cgroup_change_cgroup_flags
+//!    - cgroup_change_cgroup_uid_gid_flags
+//!
+//! Usage:
+//! ```
+//! use libcgroup_rs::cgrules::{CGRules, ProcessInfo};
+//!
+//! fn main()->Result<(),Box<dyn std::error::Error>>{
+//!     let rules = CGRules::parse("
+//!         @admin      cpu,memory  admin/
+//!         peter:cp    *           copies/%u/
+//!         *           cpu         default/
+//!     ")?;
+//!
+//!     let process = ProcessInfo::for_pid(std::process::id() as i32)?;
+//!     if let Some(m) = rules.classify(&process) {
+//!         for (ctrl,destination) in m.destinations.iter() {
+//!             println!("{} = {}",ctrl,destination);
+//!         }
+//!     }
+//!     Ok(())
+//! }
+//! ```
+//!
+//! Usage(Classify like cgclassify):
+//! ```
+//! use libcgroup_rs::initialization::CGroupInitializer;
+//! use libcgroup_rs::cgrules::RulesCache;
+//! use libcgroup_rs::templates::CGFlags;
+//!
+//! fn main()->Result<(),Box<dyn std::error::Error>>{
+//!     CGroupInitializer::init()?;
+//!     RulesCache::init()?;
+//!
+//!     for rule in RulesCache::rules_config()?.rules.iter() {
+//!         println!("Rule = {:?}",rule);
+//!     }
+//!
+//!     let pid = std::process::id() as i32;
+//!     RulesCache::change_cgroup_pid(pid,CGFlags::USE_CACHE)?;
+//!     Ok(())
+//! }
+//! ```
+//!
+
+
+use crate::prelude::*;
+use crate::error::*;
+use crate::cgconfig::{ConfigParseError, Span};
+use crate::templates::{TemplateSubst, CGFlags, group_name};
+use log::info;
+
+
+pub const CGRULES_CONF_FILE:&str = "/etc/cgrules.conf";
+pub const CGRULES_CONF_DIR:&str = "/etc/cgrules.d";
+/// Options of a rule line known to libcgroup, `ignore_rt` skips realtime processes
+pub const CGRULE_OPTIONS:&[&str] = &["ignore","ignore_rt"];
+
+
+/// Subject of a rule
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub enum RuleUser{
+    /// `*`
+    Any,
+    /// `<user>`
+    User(String),
+    /// `@<group>`
+    Group(String),
+}
+
+
+/// Controllers and destination of a rule line, `%` lines add targets to the previous rule
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct RuleTarget{
+    /// `*` stands for every controller
+    pub controllers:Vec<String>,
+    pub destination:String,
+    pub options:Vec<String>,
+    pub span:Span,
+}
+
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct CGRule{
+    pub user:RuleUser,
+    /// base name or full path of the executable
+    pub procname:Option<String>,
+    pub targets:Vec<RuleTarget>,
+    pub span:Span,
+}
+
+impl CGRule{
+
+    pub fn matches(&self,process:&ProcessInfo)->bool{
+        let user_match = match &self.user {
+            RuleUser::Any => true,
+            RuleUser::User(name) => {
+                process.user.as_deref() == Some(name.as_str()) || name == &process.uid.to_string()
+            }
+            RuleUser::Group(name) => {
+                process.groups.iter().any(|g| g == name) || name == &process.gid.to_string()
+            }
+        };
+        if !user_match {
+            return false;
+        }
+
+        match &self.procname {
+            None => true,
+            Some(procname) if procname == "*" => true,
+            Some(procname) if procname.contains('/') => process.exe.as_deref() == Some(procname.as_str()),
+            Some(procname) => {
+                let exe_name = process.exe
+                    .as_deref()
+                    .and_then(|exe| std::path::Path::new(exe).file_name())
+                    .map(|name| name.to_string_lossy().into_owned());
+                exe_name.as_deref() == Some(procname.as_str())
+                    || process.procname.as_deref() == Some(procname.as_str())
+            }
+        }
+    }
+
+    pub fn is_ignore(&self)->bool{
+        self.targets.iter().any(|t| t.options.iter().any(|o| o == "ignore"))
+    }
+
+    pub fn is_ignore_rt(&self)->bool{
+        self.targets.iter().any(|t| t.options.iter().any(|o| o == "ignore_rt"))
+    }
+
+    /// The process must stay where it is, `ignore` or `ignore_rt` for a realtime process
+    pub fn ignores(&self,process:&ProcessInfo)->bool{
+        self.is_ignore() || (self.is_ignore_rt() && process.realtime)
+    }
+}
+
+
+/// First rule matching a process, destinations are substituted
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct RuleMatch{
+    pub rule:CGRule,
+    /// controller(`*` for every controller) to destination group
+    pub destinations:std::collections::BTreeMap<String,String>,
+    /// `ignore` option or `ignore_rt` for a realtime process, the process must stay where it is
+    pub ignore:bool,
+}
+
+
+/// Process attributes used by the rules
+#[derive(Debug,Clone,PartialEq,Eq,Default)]
+pub struct ProcessInfo{
+    pub pid:i32,
+    pub uid:u32,
+    pub gid:u32,
+    pub user:Option<String>,
+    /// primary group first, then supplementary groups of the user
+    pub groups:Vec<String>,
+    /// /proc/<pid>/comm
+    pub procname:Option<String>,
+    /// /proc/<pid>/exe
+    pub exe:Option<String>,
+    /// SCHED_FIFO or SCHED_RR
+    pub realtime:bool,
+}
+
+impl ProcessInfo{
+
+    pub fn for_pid(pid:i32)->Result<Self,std::io::Error>{
+        let subst = TemplateSubst::for_pid(pid)?;
+        let exe = std::fs::read_link(format!("/proc/{}/exe",pid))
+            .ok()
+            .map(|exe| exe.to_string_lossy().into_owned());
+        let mut process = Self{
+            pid,
+            uid:subst.uid,
+            gid:subst.gid,
+            user:subst.user,
+            groups:subst.group.into_iter().collect(),
+            procname:subst.procname,
+            exe,
+            realtime:is_realtime(pid)
+        };
+        if let Some(user) = process.user.clone() {
+            process.groups.extend(supplementary_groups(&user,process.gid));
+        }
+        Ok(process)
+    }
+
+    pub fn subst(&self)->TemplateSubst{
+        TemplateSubst{
+            user:self.user.clone(),
+            uid:self.uid,
+            group:self.groups.first().cloned(),
+            gid:self.gid,
+            procname:self.procname.clone(),
+            pid:self.pid,
+        }
+    }
+}
+
+
+/// Scheduling policy of the pid is SCHED_FIFO or SCHED_RR(sched_getscheduler)
+fn is_realtime(pid:i32)->bool{
+    let policy = unsafe { libc::sched_getscheduler(pid as libc::pid_t) };
+    policy == libc::SCHED_FIFO || policy == libc::SCHED_RR
+}
+
+
+/// Names of the groups listing the user as member(getgrouplist)
+fn supplementary_groups(user:&str,gid:u32)->Vec<String>{
+    let c_user = match std::ffi::CString::new(user) {
+        Ok(c_user) => c_user,
+        Err(_) => return Vec::new(),
+    };
+    let mut ngroups:libc::c_int = 64;
+    let mut gids:Vec<libc::gid_t> = vec![0;ngroups as usize];
+    unsafe {
+        if libc::getgrouplist(c_user.as_ptr(),gid,gids.as_mut_ptr(),&mut ngroups) < 0 {
+            gids.resize(ngroups as usize,0);
+            if libc::getgrouplist(c_user.as_ptr(),gid,gids.as_mut_ptr(),&mut ngroups) < 0 {
+                return Vec::new();
+            }
+        }
+        gids.truncate(ngroups as usize);
+    }
+    gids.into_iter()
+        .filter(|g| *g != gid)
+        .filter_map(group_name)
+        .collect()
+}
+
+
+
+/// Parsed cgrules.conf, rules keep the order of the file
+#[derive(Debug,Clone,PartialEq,Eq,Default)]
+pub struct CGRules{
+    pub rules:Vec<CGRule>,
+}
+
+impl CGRules{
+
+    pub fn from_file(pathname:&str)->Result<Self,std::io::Error>{
+        Ok(Self::parse(&std::fs::read_to_string(pathname)?)?)
+    }
+
+    pub fn parse(content:&str)->Result<Self,ConfigParseError>{
+        let mut rules:Vec<CGRule> = Vec::new();
+        for (idx,raw) in content.lines().enumerate() {
+            let line = match raw.find('#') {
+                Some(comment) => &raw[..comment],
+                None => raw,
+            };
+            let mut fields = Vec::new();
+            let mut rest = line;
+            while let Some(start) = rest.find(|c:char| !c.is_whitespace()) {
+                let end = rest[start..]
+                    .find(char::is_whitespace)
+                    .map_or(rest.len(),|end| start + end);
+                let column = line.len() - rest.len() + start + 1;
+                fields.push((&rest[start..end],Span{ line:idx + 1, column }));
+                rest = &rest[end..];
+            }
+            if fields.is_empty() {
+                continue;
+            }
+
+            let span = fields[0].1;
+            if fields.len() < 3 || fields.len() > 4 {
+                return Err(ConfigParseError{
+                    span,
+                    message:format!("Expected '<user> <controllers> <destination> [<options>]', found {} fields",fields.len())
+                });
+            }
+
+            let controllers:Vec<String> = fields[1].0
+                .split(',')
+                .filter(|c| !c.is_empty())
+                .map(String::from)
+                .collect();
+            if controllers.is_empty() {
+                return Err(ConfigParseError{ span:fields[1].1, message:String::from("Missing controllers") });
+            }
+            let options = fields
+                .get(3)
+                .map(|(opts,_)| opts.split(',').map(String::from).collect())
+                .unwrap_or_default();
+            if let Some((opts,span)) = fields.get(3) {
+                if let Some(unknown) = opts.split(',').find(|o| !CGRULE_OPTIONS.contains(o)) {
+                    return Err(ConfigParseError{ span:*span, message:format!("Unknown option '{}'",unknown) });
+                }
+            }
+            let target = RuleTarget{
+                controllers,
+                destination:fields[2].0.to_string(),
+                options,
+                span
+            };
+
+            let (user,procname) = match fields[0].0.split_once(':') {
+                Some((user,procname)) => (user,Some(procname)),
+                None => (fields[0].0,None),
+            };
+            if user == "%" {
+                match rules.last_mut() {
+                    Some(rule) if procname.is_none() => rule.targets.push(target),
+                    Some(_) => return Err(ConfigParseError{
+                        span,
+                        message:String::from("Continuation rule can not set a process name")
+                    }),
+                    None => return Err(ConfigParseError{
+                        span,
+                        message:String::from("Continuation rule without previous rule")
+                    }),
+                }
+                continue;
+            }
+
+            let user = match user {
+                "*" => RuleUser::Any,
+                "" | "@" => return Err(ConfigParseError{ span, message:String::from("Missing user") }),
+                group if group.starts_with('@') => RuleUser::Group(group[1..].to_string()),
+                name => RuleUser::User(name.to_string()),
+            };
+            if procname == Some("") {
+                return Err(ConfigParseError{ span, message:String::from("Missing process name") });
+            }
+            rules.push(CGRule{
+                user,
+                procname:procname.map(String::from),
+                targets:vec![target],
+                span
+            });
+        }
+        Ok(Self{ rules })
+    }
+
+    /// Destination per controller of the first matching rule, as cgrulesengd does
+    pub fn classify(&self,process:&ProcessInfo)->Option<RuleMatch>{
+        let rule = self.rules.iter().find(|rule| rule.matches(process))?;
+        let subst = process.subst();
+        let mut destinations = std::collections::BTreeMap::new();
+        for target in rule.targets.iter() {
+            let destination = subst.substitute(&target.destination);
+            for ctrl in target.controllers.iter() {
+                destinations.entry(ctrl.clone()).or_insert_with(|| destination.clone());
+            }
+        }
+        Some(RuleMatch{
+            rule:rule.clone(),
+            destinations,
+            ignore:rule.ignores(process)
+        })
+    }
+}
+
+impl std::fmt::Display for CGRules{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for rule in self.rules.iter() {
+            let mut user = match &rule.user {
+                RuleUser::Any => String::from("*"),
+                RuleUser::User(name) => name.clone(),
+                RuleUser::Group(name) => format!("@{}",name),
+            };
+            if let Some(procname) = &rule.procname {
+                user = format!("{}:{}",user,procname);
+            }
+            for (idx,target) in rule.targets.iter().enumerate() {
+                let subject = if idx == 0 { user.as_str() } else { "%" };
+                write!(f,"{}\t{}\t{}",subject,target.controllers.join(","),target.destination)?;
+                if !target.options.is_empty() {
+                    write!(f,"\t{}",target.options.join(","))?;
+                }
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+
+
+/// Rules loaded by libcgroup, classification is done the same way as cgclassify
+///
+/// Sticky placement is not a flag of these calls, see `cgroup_register_unchanged_process`.
+pub struct RulesCache;
+
+impl RulesCache{
+
+    /// Load cgrules.conf into libcgroup(cgroup_init_rules_cache)
+    pub fn init()->Result<(),std::io::Error>{
+        unsafe {
+            let ret = cgroup_init_rules_cache();
+            info!("RulesCache::init[return code] = {}",ret);
+            if ret != C_GROUP_SUCCESS {
+                return Err(cg_get_error(ret));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn reload()->Result<(),std::io::Error>{
+        unsafe {
+            let ret = cgroup_reload_cached_rules();
+            info!("RulesCache::reload[return code] = {}",ret);
+            if ret != C_GROUP_SUCCESS {
+                return Err(cg_get_error(ret));
+            }
+        }
+        Ok(())
+    }
+
+    /// Move the pid to the destination of the first rule matching uid/gid/procname(cgroup_change_cgroup_flags)
+    pub fn change_cgroup(
+        uid:u32,
+        gid:u32,
+        procname:Option<&str>,
+        pid:i32,
+        flags:CGFlags
+    )->Result<(),std::io::Error>{
+        unsafe {
+            let c_procname = match procname {
+                Some(procname) => Some(std::ffi::CString::new(procname)?),
+                None => None,
+            };
+            let ret = cgroup_change_cgroup_flags(
+                uid as libc::uid_t,
+                gid as libc::gid_t,
+                c_procname.as_ref().map_or(std::ptr::null(),|p| p.as_ptr()),
+                pid as libc::pid_t,
+                flags.bits()
+            );
+            info!("RulesCache::change_cgroup[return code] = {}",ret);
+            if ret != C_GROUP_SUCCESS {
+                return Err(cg_get_error(ret));
+            }
+        }
+        Ok(())
+    }
+
+    /// Rules matching only uid/gid(cgroup_change_cgroup_uid_gid_flags)
+    pub fn change_cgroup_uid_gid(uid:u32,gid:u32,pid:i32,flags:CGFlags)->Result<(),std::io::Error>{
+        unsafe {
+            let ret = cgroup_change_cgroup_uid_gid_flags(
+                uid as libc::uid_t,
+                gid as libc::gid_t,
+                pid as libc::pid_t,
+                flags.bits()
+            );
+            info!("RulesCache::change_cgroup_uid_gid[return code] = {}",ret);
+            if ret != C_GROUP_SUCCESS {
+                return Err(cg_get_error(ret));
+            }
+        }
+        Ok(())
+    }
+
+    /// Classify a running pid with its effective ids and executable path
+    pub fn change_cgroup_pid(pid:i32,flags:CGFlags)->Result<(),std::io::Error>{
+        let process = ProcessInfo::for_pid(pid)?;
+        let procname = process.exe.as_deref().or(process.procname.as_deref());
+        Self::change_cgroup(process.uid,process.gid,procname,pid,flags)
+    }
+
+    /// Rules as data instead of cgroup_print_rules_config,
+    /// read from the same files as libcgroup(cgrules.conf then cgrules.d/*.conf)
+    pub fn rules_config()->Result<CGRules,std::io::Error>{
+        let mut rules = match std::fs::read_to_string(CGRULES_CONF_FILE) {
+            Ok(content) => CGRules::parse(&content)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => CGRules::default(),
+            Err(e) => return Err(e),
+        };
+
+        let mut files:Vec<std::path::PathBuf> = match std::fs::read_dir(CGRULES_CONF_DIR) {
+            Ok(dir) => dir
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.extension().map_or(false,|ext| ext == "conf"))
+                .collect(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        files.sort();
+        for path in files {
+            let content = std::fs::read_to_string(&path)?;
+            let parsed = CGRules::parse(&content).map_err(|e| std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("{}:{}",path.display(),e)
+            ))?;
+            rules.rules.extend(parsed.rules);
+        }
+        Ok(rules)
+    }
+}
@@ -6,15 +6,51 @@
 //! Functions:
 //!    - cgroup_config_load_config
 //!    - cgroup_unload_cgroups
+//!    - cgroup_config_unload_config
+//!    - cgroup_config_set_default
 //!
 //! Usage:
 //! ```
 //! use libcgroup_rs::config::Loader;
+//! use libcgroup_rs::manipulation::DeleteFlags;
 //!
 //! fn main()->Result<(),Box<dyn std::error::Error>>{
 //!
 //!     assert!(Loader::load("/etc/cgroup.conf").is_ok());
-//!     assert!(Loader::unload().is_ok());
+//!     assert!(Loader::unload_file("/etc/cgroup.conf",DeleteFlags::IGNORE_MIGRATION).is_ok());
+//!     Ok(())
+//! }
+//! ```
+//!
+//! Usage(Default permissions):
+//! ```
+//! use libcgroup_rs::config::{Loader, DefaultPermissions};
+//!
+//! fn main()->Result<(),Box<dyn std::error::Error>>{
+//!
+//!     Loader::set_default(&DefaultPermissions{
+//!         tasks_uid:Some(1000),
+//!         tasks_gid:Some(1000),
+//!         dir_mode:Some(0o775),
+//!         tasks_mode:Some(0o664),
+//!         ..DefaultPermissions::default()
+//!     })?;
+//!     Loader::load("/etc/cgconfig.conf")?;
+//!     Ok(())
+//! }
+//! ```
+//!
+//! Usage(Dry-run):
+//! ```
+//! use libcgroup_rs::config::Loader;
+//!
+//! fn main()->Result<(),Box<dyn std::error::Error>>{
+//!
+//!     let diagnostics = Loader::validate("/etc/cgconfig.conf")?;
+//!     for d in diagnostics.iter() {
+//!         println!("{}",d);
+//!     }
+//!     assert!(!diagnostics.iter().any(|d| d.is_error()));
 //!     Ok(())
 //! }
 //! ```
@@ -22,7 +58,105 @@
 
 
 use crate::prelude::*;
-use crate::error::{C_GROUP_SUCCESS, cg_get_error};
+use log::info;
+use crate::error::{C_GROUP_SUCCESS, C_EC_GROUP_NOT_CREATED, cg_get_error};
+use crate::manipulation::{DeleteFlags, Permissions};
+use crate::cgconfig::{CGConfig, ConfigItem, GroupBlock, PermBlock, Span};
+use crate::extend::{CGroupMount, CGroupVersion, ProcCGroup};
+
+/// Owners and modes of the groups created without explicit permissions, `None` keeps the system default
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+pub struct DefaultPermissions{
+    pub tasks_uid:Option<u32>,
+    pub tasks_gid:Option<u32>,
+    pub admin_uid:Option<u32>,
+    pub admin_gid:Option<u32>,
+    /// mode of the group directory
+    pub dir_mode:Option<u32>,
+    /// mode of the control files
+    pub file_mode:Option<u32>,
+    /// mode of the tasks file
+    pub tasks_mode:Option<u32>,
+}
+
+impl DefaultPermissions{
+
+    /// Permissions of a `perm` block, owners are resolved to ids
+    pub fn from_perm(perm:&PermBlock)->Result<Self,std::io::Error>{
+        let owner = |value:Option<&String>,resolve:fn(&str)->Option<u32>| -> Result<Option<u32>,std::io::Error> {
+            match value {
+                Some(v) => resolve(v)
+                    .map(Some)
+                    .ok_or_else(|| std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        format!("Unknown owner = {}",v)
+                    )),
+                None => Ok(None)
+            }
+        };
+        let mode = |value:Option<&String>| -> Result<Option<u32>,std::io::Error> {
+            match value {
+                Some(v) => u32::from_str_radix(v,8)
+                    .map(Some)
+                    .map_err(|_| std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("Invalid mode = {}",v)
+                    )),
+                None => Ok(None)
+            }
+        };
+
+        let task = perm.task.clone().unwrap_or_default();
+        let admin = perm.admin.clone().unwrap_or_default();
+        Ok(Self{
+            tasks_uid:owner(task.uid.as_ref(),resolve_uid)?,
+            tasks_gid:owner(task.gid.as_ref(),resolve_gid)?,
+            admin_uid:owner(admin.uid.as_ref(),resolve_uid)?,
+            admin_gid:owner(admin.gid.as_ref(),resolve_gid)?,
+            dir_mode:mode(admin.dperm.as_ref())?,
+            file_mode:mode(admin.fperm.as_ref())?,
+            tasks_mode:mode(task.fperm.as_ref())?,
+        })
+    }
+
+    pub fn modes(&self)->Permissions{
+        Permissions{
+            dir_mode:self.dir_mode,
+            file_mode:self.file_mode,
+            tasks_mode:self.tasks_mode,
+        }
+    }
+
+    /// cgroup_set_uid_gid, NO_UID_GID for unset owners
+    pub(crate) fn apply_owner(&self,cg:*mut cgroup)->Result<(),std::io::Error>{
+        let id = |v:Option<u32>| v.unwrap_or(u32::MAX) as libc::uid_t;
+        unsafe {
+            let ret = cgroup_set_uid_gid(
+                cg,
+                id(self.tasks_uid),
+                id(self.tasks_gid),
+                id(self.admin_uid),
+                id(self.admin_gid)
+            );
+            info!("DefaultPermissions::apply_owner[return code] = {}",ret);
+            if ret != C_GROUP_SUCCESS {
+                return Err(cg_get_error(ret));
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn apply(&self,cg:*mut cgroup)->Result<(),std::io::Error>{
+        self.apply_owner(cg)?;
+        self.modes().apply(cg);
+        Ok(())
+    }
+}
+
+
+static DEFAULT_PERMISSIONS:std::sync::Mutex<Option<DefaultPermissions>> = std::sync::Mutex::new(None);
+
+
 
 pub struct Loader{}
 
@@ -39,6 +173,7 @@ impl Loader{
         Ok(())
     }
 
+    /// Delete every group on the machine(cgroup_unload_cgroups), see `unload_file`
     pub fn unload()->Result<(),std::io::Error>{
         unsafe {
             let ret = cgroup_unload_cgroups();
@@ -48,4 +183,393 @@ impl Loader{
         }
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Permissions of the groups without `perm` block(cgroup_config_set_default),
+    /// also used by `CGroupBuilder::create` when no owner was set
+    pub fn set_default(perms:&DefaultPermissions)->Result<(),std::io::Error>{
+        unsafe {
+            let c_name = std::ffi::CString::new("default")?;
+            let cg = cgroup_new_cgroup(c_name.as_ptr());
+            if cg.is_null() {
+                return Err(cg_get_error(C_EC_GROUP_NOT_CREATED));
+            }
+            let ret = perms.apply(cg).map(|_| cgroup_config_set_default(cg));
+            let c_point = cg as *const cgroup;
+            cgroup_free(&c_point as *const *const cgroup);
+
+            let ret = ret?;
+            info!("Loader::set_default[return code] = {}",ret);
+            if ret != C_GROUP_SUCCESS {
+                return Err(cg_get_error(ret));
+            }
+        }
+        *DEFAULT_PERMISSIONS.lock().unwrap_or_else(|e| e.into_inner()) = Some(*perms);
+        Ok(())
+    }
+
+    pub fn get_default()->Option<DefaultPermissions>{
+        *DEFAULT_PERMISSIONS.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Delete only the groups defined in the config(cgroup_config_unload_config)
+    pub fn unload_file(pathname:&str,flags:DeleteFlags)->Result<(),std::io::Error>{
+        unsafe {
+            let c_pathname = std::ffi::CString::new(pathname)?;
+            let ret = cgroup_config_unload_config(c_pathname.as_ptr(),flags.bits());
+            info!("Loader::unload_file[return code] = {}",ret);
+            if ret != C_GROUP_SUCCESS {
+                return Err(cg_get_error(ret))
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse and check a config without touching the hierarchy
+    ///
+    /// Controllers must be known by the kernel and mounted(by the config or the system),
+    /// parameters must exist under the mount point, owners must resolve to uid/gid.
+    pub fn validate(pathname:&str)->Result<Vec<Diagnostic>,std::io::Error>{
+        let content = std::fs::read_to_string(pathname)?;
+        match CGConfig::parse(&content) {
+            Ok(config) => Self::validate_config(&config),
+            Err(e) => Ok(vec![Diagnostic::error(e.span,e.message)]),
+        }
+    }
+
+    pub fn validate_config(config:&CGConfig)->Result<Vec<Diagnostic>,std::io::Error>{
+        Ok(Self::validate_with(config,&SystemHierarchy::from_system()?))
+    }
+
+    /// Check a config against the given hierarchy instead of the one of the machine
+    pub fn validate_with(config:&CGConfig,system:&SystemHierarchy)->Vec<Diagnostic>{
+        let system = system.with_config(config);
+        let mut diagnostics = Vec::new();
+        for item in config.items.iter() {
+            match item {
+                ConfigItem::Mount(m) => {
+                    for entry in m.entries.iter() {
+                        if !entry.key.starts_with("name=") && !system.is_known(&entry.key) {
+                            diagnostics.push(Diagnostic::error(
+                                entry.span,
+                                format!("Unknown controller '{}'",entry.key)
+                            ));
+                        }
+                    }
+                }
+                ConfigItem::Namespace(n) => {
+                    for entry in n.entries.iter() {
+                        if !system.is_known(&entry.key) {
+                            diagnostics.push(Diagnostic::error(
+                                entry.span,
+                                format!("Unknown controller '{}'",entry.key)
+                            ));
+                        }
+                    }
+                }
+                ConfigItem::Group(g) => system.check_group(g,false,&mut diagnostics),
+                ConfigItem::Template(t) => system.check_group(t,true,&mut diagnostics),
+                ConfigItem::Default(d) => check_perm(&d.perm,false,&mut diagnostics),
+            }
+        }
+        diagnostics
+    }
+}
+
+
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum Severity{
+    Error,
+    Warning
+}
+
+
+/// Problem found by `Loader::validate`
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct Diagnostic{
+    pub severity:Severity,
+    pub span:Span,
+    pub message:String,
+}
+
+impl Diagnostic{
+    fn error(span:Span,message:String)->Self{
+        Self{ severity:Severity::Error, span, message }
+    }
+
+    fn warning(span:Span,message:String)->Self{
+        Self{ severity:Severity::Warning, span, message }
+    }
+
+    pub fn is_error(&self)->bool{
+        self.severity == Severity::Error
+    }
+}
+
+impl std::fmt::Display for Diagnostic{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f,"{} {}: {}",severity,self.span,self.message)
+    }
+}
+
+
+
+/// Controllers and mount points seen by the validation
+#[derive(Debug,Clone,PartialEq,Eq,Default)]
+pub struct SystemHierarchy{
+    known:Vec<String>,
+    mounts:Vec<(String,std::path::PathBuf)>,
+    /// cgroup2 mount points, the root group has no controller interface files
+    unified:Vec<std::path::PathBuf>,
+}
+
+impl SystemHierarchy{
+
+    /// Empty hierarchy, controllers are added with `add_controller`
+    pub fn new()->Self{
+        Self::default()
+    }
+
+    /// Controllers enabled in /proc/cgroups and mounted in /proc/self/mountinfo
+    pub fn from_system()->Result<Self,std::io::Error>{
+        let mut known:Vec<String> = ProcCGroup::from_proc()?
+            .into_iter()
+            .filter(|s| s.enabled)
+            .map(|s| s.subsys_name)
+            .collect();
+        let mut mounts = Vec::new();
+        let mut unified = Vec::new();
+
+        for m in CGroupMount::from_self()? {
+            match m.version {
+                CGroupVersion::V1 => {
+                    for ctrl in m.controllers.iter() {
+                        mounts.push((ctrl.clone(),std::path::PathBuf::from(&m.mount_point)));
+                    }
+                }
+                CGroupVersion::V2 => {
+                    unified.push(std::path::PathBuf::from(&m.mount_point));
+                    let path = std::path::Path::new(&m.mount_point).join("cgroup.controllers");
+                    if let Ok(content) = std::fs::read_to_string(path) {
+                        for ctrl in content.split_whitespace() {
+                            if !known.iter().any(|k| k == ctrl) {
+                                known.push(ctrl.to_string());
+                            }
+                            mounts.push((ctrl.to_string(),std::path::PathBuf::from(&m.mount_point)));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(Self{ known, mounts, unified })
+    }
+
+    /// Known controller, mounted at `mount_point` when there is one
+    pub fn add_controller(&mut self,ctrl_name:&str,mount_point:Option<&str>)->&mut Self{
+        if !self.is_known(ctrl_name) {
+            self.known.push(ctrl_name.to_string());
+        }
+        if let Some(mount_point) = mount_point {
+            self.mounts.push((ctrl_name.to_string(),std::path::PathBuf::from(mount_point)));
+        }
+        self
+    }
+
+    /// cgroup2 mount point
+    pub fn add_unified(&mut self,mount_point:&str)->&mut Self{
+        self.unified.push(std::path::PathBuf::from(mount_point));
+        self
+    }
+
+    /// Mount block of the config takes precedence over the mounts of the hierarchy
+    fn with_config(&self,config:&CGConfig)->Self{
+        let mut mounts:Vec<(String,std::path::PathBuf)> = config.mounts()
+            .flat_map(|m| m.entries.iter())
+            .map(|entry| (entry.key.clone(),std::path::PathBuf::from(&entry.value)))
+            .collect();
+        mounts.extend(self.mounts.iter().cloned());
+        Self{ known:self.known.clone(), mounts, unified:self.unified.clone() }
+    }
+
+    fn is_known(&self,ctrl_name:&str)->bool{
+        self.known.iter().any(|k| k == ctrl_name)
+    }
+
+    fn mount_point(&self,ctrl_name:&str)->Option<&std::path::Path>{
+        self.mounts
+            .iter()
+            .find(|(ctrl,_)| ctrl == ctrl_name)
+            .map(|(_,path)| path.as_path())
+    }
+
+    /// On cgroup2 the file is looked up in the child groups having the controller enabled,
+    /// the parameter is not checked when there is none
+    fn has_param(&self,mount_point:&std::path::Path,ctrl_name:&str,key:&str)->bool{
+        if mount_point.join(key).exists() {
+            return true;
+        }
+        if !self.unified.iter().any(|p| p == mount_point) {
+            return false;
+        }
+        let children:Vec<std::path::PathBuf> = match std::fs::read_dir(mount_point) {
+            Ok(entries) => entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| std::fs::read_to_string(path.join("cgroup.controllers"))
+                    .map_or(false,|c| c.split_whitespace().any(|c| c == ctrl_name)))
+                .collect(),
+            Err(_) => return true,
+        };
+        children.is_empty() || children.iter().any(|path| path.join(key).exists())
+    }
+
+    fn check_group(&self,group:&GroupBlock,template:bool,diagnostics:&mut Vec<Diagnostic>){
+        if let Some(perm) = &group.perm {
+            check_perm(perm,template,diagnostics);
+        }
+
+        for ctrl in group.controllers.iter() {
+            if !self.is_known(&ctrl.name) {
+                diagnostics.push(Diagnostic::error(ctrl.span,format!("Unknown controller '{}'",ctrl.name)));
+                continue;
+            }
+            let mount_point = match self.mount_point(&ctrl.name) {
+                Some(path) => path,
+                None => {
+                    diagnostics.push(Diagnostic::error(ctrl.span,format!("Controller '{}' is not mounted",ctrl.name)));
+                    continue;
+                }
+            };
+            // hierarchy mounted by this config, parameter files are not there yet
+            let mounted = mount_point.join("cgroup.procs").exists();
+
+            for param in ctrl.params.iter() {
+                if !param.key.starts_with(&format!("{}.",ctrl.name)) {
+                    diagnostics.push(Diagnostic::warning(
+                        param.span,
+                        format!("Parameter '{}' does not belong to controller '{}'",param.key,ctrl.name)
+                    ));
+                }
+                if mounted && !self.has_param(mount_point,&ctrl.name,&param.key) {
+                    diagnostics.push(Diagnostic::error(
+                        param.span,
+                        format!("Parameter '{}' not found under {}",param.key,mount_point.display())
+                    ));
+                }
+                if let Some(message) = check_value(&param.key,&param.value) {
+                    diagnostics.push(Diagnostic::error(param.span,message));
+                }
+            }
+        }
+    }
+}
+
+
+/// Template owners may use %u/%g substitution, resolved when the group is created
+fn check_perm(perm:&PermBlock,template:bool,diagnostics:&mut Vec<Diagnostic>){
+    let owners = perm.task
+        .iter()
+        .flat_map(|t| [(t.uid.as_ref(),t.span,true),(t.gid.as_ref(),t.span,false)])
+        .chain(perm.admin
+            .iter()
+            .flat_map(|a| [(a.uid.as_ref(),a.span,true),(a.gid.as_ref(),a.span,false)]));
+
+    for (owner,span,is_user) in owners {
+        let owner = match owner {
+            Some(owner) => owner,
+            None => continue,
+        };
+        if template && owner.starts_with('%') {
+            continue;
+        }
+        let resolved = if is_user { resolve_uid(owner).is_some() } else { resolve_gid(owner).is_some() };
+        if !resolved {
+            diagnostics.push(Diagnostic::error(
+                span,
+                format!("Unknown {} '{}'",if is_user { "user" } else { "group" },owner)
+            ));
+        }
+    }
+}
+
+
+pub fn resolve_uid(user:&str)->Option<u32>{
+    if let Ok(uid) = user.parse::<u32>() {
+        return Some(uid);
+    }
+    let c_user = std::ffi::CString::new(user).ok()?;
+    lookup_r(|buf| unsafe {
+        let mut pw:libc::passwd = std::mem::zeroed();
+        let mut result:*mut libc::passwd = std::ptr::null_mut();
+        let ret = libc::getpwnam_r(c_user.as_ptr(),&mut pw,buf.as_mut_ptr(),buf.len(),&mut result);
+        if ret != 0 {
+            return Err(ret);
+        }
+        Ok(if result.is_null() { None } else { Some(pw.pw_uid) })
+    })
+}
+
+
+pub fn resolve_gid(group:&str)->Option<u32>{
+    if let Ok(gid) = group.parse::<u32>() {
+        return Some(gid);
+    }
+    let c_group = std::ffi::CString::new(group).ok()?;
+    lookup_r(|buf| unsafe {
+        let mut gr:libc::group = std::mem::zeroed();
+        let mut result:*mut libc::group = std::ptr::null_mut();
+        let ret = libc::getgrnam_r(c_group.as_ptr(),&mut gr,buf.as_mut_ptr(),buf.len(),&mut result);
+        if ret != 0 {
+            return Err(ret);
+        }
+        Ok(if result.is_null() { None } else { Some(gr.gr_gid) })
+    })
+}
+
+
+/// Run a reentrant passwd/group lookup(getpwnam_r, ...) with a buffer grown on ERANGE,
+/// errors and unknown entries give None
+pub(crate) fn lookup_r<T,F>(mut lookup:F)->Option<T>
+where F:FnMut(&mut [libc::c_char])->Result<Option<T>,libc::c_int>
+{
+    let mut buf:Vec<libc::c_char> = vec![0;1024];
+    loop {
+        match lookup(&mut buf) {
+            Ok(found) => return found,
+            Err(libc::ERANGE) if buf.len() < 1 << 20 => {
+                let len = buf.len() * 2;
+                buf.resize(len,0);
+            }
+            Err(_) => return None,
+        }
+    }
+}
+
+
+/// Numeric parameters by suffix, other values are only checked for control characters
+fn check_value(key:&str,value:&str)->Option<String>{
+    if value.chars().any(|c| c.is_control()) {
+        return Some(format!("Value of '{}' contains control characters",key));
+    }
+
+    let numeric = [".shares",".weight","_us","_in_bytes",".swappiness"]
+        .iter()
+        .any(|suffix| key.ends_with(suffix));
+    if !numeric || value == "max" || value == "-1" {
+        return None;
+    }
+
+    let digits = if key.ends_with("_in_bytes") {
+        value.trim_end_matches(['k','K','m','M','g','G'])
+    } else {
+        value
+    };
+    if digits.parse::<u64>().is_err() {
+        return Some(format!("Value of '{}' is not a number = {}",key,value));
+    }
+    None
+}
@@ -0,0 +1,225 @@
+//!
+//! ### Diff of groups
+//!
+//! Structural differences between two states of a group or of a tree,
+//! listed in the order they would be applied(parents created first, children removed first).
+//!
+//! Usage(Review before modify):
+//! ```
+//! use libcgroup_rs::initialization::CGroupInitializer;
+//! use libcgroup_rs::manipulation::CGroupBuilder;
+//! use libcgroup_rs::diff::CGroupDiff;
+//!
+//! fn main()->Result<(),Box<dyn std::error::Error>>{
+//!     CGroupInitializer::init()?;
+//!
+//!     let current = CGroupBuilder::new("foo")?;
+//!     current.load()?;
+//!
+//!     let desired = CGroupBuilder::new("foo")?;
+//!     desired.add_controller("cpu")?.add_u64("cpu.shares",512)?;
+//!
+//!     let diff = CGroupDiff::between(&current,&desired)?;
+//!     // ~ foo cpu.shares: 1024 -> 512
+//!     print!("{}",diff);
+//!     if !diff.is_empty() {
+//!         desired.modify()?;
+//!     }
+//!     Ok(())
+//! }
+//! ```
+//!
+
+
+use crate::manipulation::CGroupBuilder;
+use crate::tree::{CGroupTree, CGroupNode};
+
+
+/// Controller name to parameter values
+pub type ControllerValues = std::collections::BTreeMap<String,std::collections::BTreeMap<String,String>>;
+
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub enum ChangeKind{
+    AddGroup,
+    RemoveGroup,
+    AddController(String),
+    RemoveController(String),
+    AddValue{controller:String,name:String,value:String},
+    RemoveValue{controller:String,name:String,value:String},
+    ChangeValue{controller:String,name:String,old:String,new:String},
+}
+
+
+/// One step of a change plan
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct Change{
+    pub group:String,
+    pub kind:ChangeKind,
+}
+
+impl std::fmt::Display for Change{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            ChangeKind::AddGroup => write!(f,"+ {}",self.group),
+            ChangeKind::RemoveGroup => write!(f,"- {}",self.group),
+            ChangeKind::AddController(ctrl) => write!(f,"+ {} {}",self.group,ctrl),
+            ChangeKind::RemoveController(ctrl) => write!(f,"- {} {}",self.group,ctrl),
+            ChangeKind::AddValue{name,value,..} => write!(f,"+ {} {} = {}",self.group,name,value),
+            ChangeKind::RemoveValue{name,value,..} => write!(f,"- {} {} = {}",self.group,name,value),
+            ChangeKind::ChangeValue{name,old,new,..} => write!(f,"~ {} {}: {} -> {}",self.group,name,old,new),
+        }
+    }
+}
+
+
+/// Changes turning an old state into a new one
+#[derive(Debug,Clone,PartialEq,Eq,Default)]
+pub struct CGroupDiff{
+    pub changes:Vec<Change>,
+}
+
+impl CGroupDiff{
+
+    /// Values set in `new` against a group in memory, see `CGroupBuilder::load` for the current state,
+    /// the other controllers and values of `old` are left out as `CGroupBuilder::modify` keeps them
+    pub fn between(old:&CGroupBuilder,new:&CGroupBuilder)->Result<Self,std::io::Error>{
+        Ok(Self::between_set(new.get_name(),&old.get_values()?,&new.get_values()?))
+    }
+
+    /// Diff of the controllers and values present in `new` only
+    pub fn between_set(group:&str,old:&ControllerValues,new:&ControllerValues)->Self{
+        let old:ControllerValues = old.iter()
+            .filter_map(|(ctrl,old_values)| {
+                let new_values = new.get(ctrl)?;
+                let old_values = old_values.iter()
+                    .filter(|(name,_)| new_values.contains_key(*name))
+                    .map(|(name,value)| (name.clone(),value.clone()))
+                    .collect();
+                Some((ctrl.clone(),old_values))
+            })
+            .collect();
+        Self::between_values(group,&old,new)
+    }
+
+    /// Diff of the controllers of one group
+    pub fn between_values(group:&str,old:&ControllerValues,new:&ControllerValues)->Self{
+        let mut diff = Self::default();
+        diff.diff_controllers(group,old,new);
+        diff
+    }
+
+    /// Groups present in only one tree and values of the groups in both,
+    /// values are compared only when both nodes were built with them
+    pub fn between_trees(old:&CGroupTree,new:&CGroupTree)->Self{
+        let mut diff = Self::default();
+
+        let mut removed:Vec<&CGroupNode> = old.iter()
+            .filter(|node| new.find(&node.path).is_none())
+            .collect();
+        // children before parents
+        removed.reverse();
+        for node in removed {
+            diff.push(&node.path,ChangeKind::RemoveGroup);
+        }
+
+        for node in new.iter() {
+            let values = |tree:&CGroupTree,node:&CGroupNode| {
+                let mut values = ControllerValues::new();
+                if !node.values.is_empty() {
+                    values.insert(tree.ctrl_name.clone(),node.values.clone());
+                }
+                values
+            };
+            match old.find(&node.path) {
+                Some(old_node) if old_node.values.is_empty() || node.values.is_empty() => (),
+                Some(old_node) => {
+                    diff.diff_controllers(&node.path,&values(old,old_node),&values(new,node));
+                }
+                None => {
+                    diff.push(&node.path,ChangeKind::AddGroup);
+                    diff.diff_controllers(&node.path,&ControllerValues::new(),&values(new,node));
+                }
+            }
+        }
+        diff
+    }
+
+    fn push(&mut self,group:&str,kind:ChangeKind){
+        self.changes.push(Change{ group:group.to_string(), kind });
+    }
+
+    fn diff_controllers(&mut self,group:&str,old:&ControllerValues,new:&ControllerValues){
+        let empty = std::collections::BTreeMap::new();
+        for (ctrl,old_values) in old.iter() {
+            if !new.contains_key(ctrl) {
+                for (name,value) in old_values.iter() {
+                    self.push(group,ChangeKind::RemoveValue{
+                        controller:ctrl.clone(),
+                        name:name.clone(),
+                        value:value.clone()
+                    });
+                }
+                self.push(group,ChangeKind::RemoveController(ctrl.clone()));
+            }
+        }
+
+        for (ctrl,new_values) in new.iter() {
+            let old_values = match old.get(ctrl) {
+                Some(old_values) => old_values,
+                None => {
+                    self.push(group,ChangeKind::AddController(ctrl.clone()));
+                    &empty
+                }
+            };
+            for (name,value) in new_values.iter() {
+                let kind = match old_values.get(name) {
+                    Some(old_value) if old_value == value => continue,
+                    Some(old_value) => ChangeKind::ChangeValue{
+                        controller:ctrl.clone(),
+                        name:name.clone(),
+                        old:old_value.clone(),
+                        new:value.clone()
+                    },
+                    None => ChangeKind::AddValue{
+                        controller:ctrl.clone(),
+                        name:name.clone(),
+                        value:value.clone()
+                    },
+                };
+                self.push(group,kind);
+            }
+            for (name,value) in old_values.iter() {
+                if !new_values.contains_key(name) {
+                    self.push(group,ChangeKind::RemoveValue{
+                        controller:ctrl.clone(),
+                        name:name.clone(),
+                        value:value.clone()
+                    });
+                }
+            }
+        }
+    }
+
+    pub fn is_empty(&self)->bool{
+        self.changes.is_empty()
+    }
+
+    pub fn len(&self)->usize{
+        self.changes.len()
+    }
+
+    /// Changes of one group
+    pub fn get_group(&self,group:&str)->Vec<&Change>{
+        self.changes.iter().filter(|change| change.group == group).collect()
+    }
+}
+
+impl std::fmt::Display for CGroupDiff{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for change in self.changes.iter() {
+            writeln!(f,"{}",change)?;
+        }
+        Ok(())
+    }
+}
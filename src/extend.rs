@@ -1,10 +1,116 @@
-use log::info;
+use log::{info,error};
+use crate::iterators::CGroupControllerIterator;
 
-pub const MNT_DEFAULT:i32 = 0x0;
-pub const MNT_FORCE:i32 = 0x1;
-pub const MNT_DETACH:i32 = 0x2;
-pub const MNT_EXPIRE:i32 = 0x4;
-pub const UMOUNT_NOFOLLOW:i32 = 0x6; //Unrealized(since Linux 2.6.34)
+pub const PROC_SELF_MOUNTINFO:&str = "/proc/self/mountinfo";
+pub const PROC_CGROUPS:&str = "/proc/cgroups";
+
+
+
+/// Flags of mount(2)
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+pub struct MountFlags(libc::c_ulong);
+
+impl MountFlags{
+    pub const NONE:MountFlags = MountFlags(0);
+    pub const RDONLY:MountFlags = MountFlags(libc::MS_RDONLY);
+    pub const NOSUID:MountFlags = MountFlags(libc::MS_NOSUID);
+    pub const NODEV:MountFlags = MountFlags(libc::MS_NODEV);
+    pub const NOEXEC:MountFlags = MountFlags(libc::MS_NOEXEC);
+    pub const SYNCHRONOUS:MountFlags = MountFlags(libc::MS_SYNCHRONOUS);
+    pub const REMOUNT:MountFlags = MountFlags(libc::MS_REMOUNT);
+    pub const DIRSYNC:MountFlags = MountFlags(libc::MS_DIRSYNC);
+    pub const NOATIME:MountFlags = MountFlags(libc::MS_NOATIME);
+    pub const NODIRATIME:MountFlags = MountFlags(libc::MS_NODIRATIME);
+    pub const BIND:MountFlags = MountFlags(libc::MS_BIND);
+    pub const MOVE:MountFlags = MountFlags(libc::MS_MOVE);
+    pub const REC:MountFlags = MountFlags(libc::MS_REC);
+    pub const SILENT:MountFlags = MountFlags(libc::MS_SILENT);
+    pub const UNBINDABLE:MountFlags = MountFlags(libc::MS_UNBINDABLE);
+    pub const PRIVATE:MountFlags = MountFlags(libc::MS_PRIVATE);
+    pub const SLAVE:MountFlags = MountFlags(libc::MS_SLAVE);
+    pub const SHARED:MountFlags = MountFlags(libc::MS_SHARED);
+    pub const RELATIME:MountFlags = MountFlags(libc::MS_RELATIME);
+    pub const STRICTATIME:MountFlags = MountFlags(libc::MS_STRICTATIME);
+    pub const LAZYTIME:MountFlags = MountFlags(libc::MS_LAZYTIME);
+
+    pub fn bits(&self)->libc::c_ulong{
+        self.0
+    }
+
+    pub fn from_bits(bits:libc::c_ulong)->Self{
+        MountFlags(bits)
+    }
+
+    pub fn contains(&self,other:MountFlags)->bool{
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for MountFlags{
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        MountFlags(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitOrAssign for MountFlags{
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+
+
+/// Flags of umount2(2)
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+pub struct UmountFlags(libc::c_int);
+
+impl UmountFlags{
+    pub const NONE:UmountFlags = UmountFlags(0);
+    pub const FORCE:UmountFlags = UmountFlags(libc::MNT_FORCE);
+    pub const DETACH:UmountFlags = UmountFlags(libc::MNT_DETACH);
+    pub const EXPIRE:UmountFlags = UmountFlags(libc::MNT_EXPIRE);
+    /// since Linux 2.6.34
+    pub const NOFOLLOW:UmountFlags = UmountFlags(libc::UMOUNT_NOFOLLOW);
+
+    pub fn bits(&self)->libc::c_int{
+        self.0
+    }
+
+    pub fn from_bits(bits:libc::c_int)->Self{
+        UmountFlags(bits)
+    }
+
+    pub fn contains(&self,other:UmountFlags)->bool{
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for UmountFlags{
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        UmountFlags(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitOrAssign for UmountFlags{
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+#[deprecated(note = "use UmountFlags::NONE")]
+pub const MNT_DEFAULT:UmountFlags = UmountFlags::NONE;
+#[deprecated(note = "use UmountFlags::FORCE")]
+pub const MNT_FORCE:UmountFlags = UmountFlags::FORCE;
+#[deprecated(note = "use UmountFlags::DETACH")]
+pub const MNT_DETACH:UmountFlags = UmountFlags::DETACH;
+#[deprecated(note = "use UmountFlags::EXPIRE")]
+pub const MNT_EXPIRE:UmountFlags = UmountFlags::EXPIRE;
+#[deprecated(note = "use UmountFlags::NOFOLLOW")]
+pub const UMOUNT_NOFOLLOW:UmountFlags = UmountFlags::NOFOLLOW;
 
 
 
@@ -12,8 +118,9 @@ pub struct MountBuilder<'a,'b,'c,'d>{
     src_path:&'a str,
     target_path:&'b str,
     type_name:&'c str,
-    flags:u64,
-    opts:&'d str
+    flags:MountFlags,
+    opts:std::borrow::Cow<'d,str>,
+    fs_context:bool
 }
 
 impl<'a,'b,'c,'d> MountBuilder<'a,'b,'c,'d>{
@@ -22,11 +129,46 @@ impl<'a,'b,'c,'d> MountBuilder<'a,'b,'c,'d>{
             src_path:"",
             target_path:"",
             type_name:"",
-            flags:0,
-            opts:""
+            flags:MountFlags::NONE,
+            opts:std::borrow::Cow::Borrowed(""),
+            fs_context:false
         }
     }
 
+    /// Unified hierarchy(mount -t cgroup2 -o <opts> none <target_path>)
+    pub fn cgroup2(target_path:&'b str,opts:&[CGroup2Option])->Self{
+        let mut space = Self::new();
+        space.target_path = target_path;
+        space.src_path = "none";
+        space.type_name = "cgroup2";
+        space.opts = std::borrow::Cow::Owned(opts
+            .iter()
+            .map(|opt| opt.as_str())
+            .collect::<Vec<&str>>()
+            .join(","));
+        space
+    }
+
+    /// v1 hierarchy with co-mounted controllers(mount -t cgroup -o cpu,cpuacct cgroup <target_path>)
+    pub fn cgroup_v1(target_path:&'b str,controllers:&[&str])->Self{
+        let mut space = Self::new();
+        space.target_path = target_path;
+        space.src_path = "cgroup";
+        space.type_name = "cgroup";
+        space.opts = std::borrow::Cow::Owned(controllers.join(","));
+        space
+    }
+
+    /// v1 named hierarchy without controller(mount -t cgroup -o none,name=<name> none <target_path>)
+    pub fn named(target_path:&'b str,name:&str)->Self{
+        let mut space = Self::new();
+        space.target_path = target_path;
+        space.src_path = "none";
+        space.type_name = "cgroup";
+        space.opts = std::borrow::Cow::Owned(format!("none,name={}",name));
+        space
+    }
+
 
     pub fn set_src_path(&mut self,src_path:&'a str)->&mut Self{
         self.src_path = src_path;
@@ -43,16 +185,83 @@ impl<'a,'b,'c,'d> MountBuilder<'a,'b,'c,'d>{
         self
     }
 
-    pub fn set_flags(&mut self,flags:u64)->&mut Self{
+    pub fn set_flags(&mut self,flags:MountFlags)->&mut Self{
         self.flags = flags;
         self
     }
 
     pub fn set_opts(&mut self,opts:&'d str)->&mut Self{
-        self.opts = opts;
+        self.opts = std::borrow::Cow::Borrowed(opts);
+        self
+    }
+
+    /// Mount through the new mount API and log the kernel error messages,
+    /// mount(2) is still used on kernels without fsopen(2)
+    pub fn set_fs_context(&mut self,fs_context:bool)->&mut Self{
+        self.fs_context = fs_context;
         self
     }
 
+    pub fn get_opts(&self)->&str{
+        self.opts.as_ref()
+    }
+
+    /// Check cgroup/cgroup2 options before mounting, controllers must be enabled in /proc/cgroups
+    pub fn validate(&self)->Result<(),std::io::Error>{
+        match self.type_name {
+            "cgroup" => {
+                let mut controllers = Vec::new();
+                let mut name = None;
+                for opt in self.opts.split(',').filter(|opt| !opt.is_empty()) {
+                    if let Some(n) = opt.strip_prefix("name=") {
+                        name = Some(n);
+                    } else if !is_v1_generic_opt(opt) {
+                        controllers.push(opt);
+                    }
+                }
+
+                if let Some(n) = name {
+                    if n.is_empty() || n.contains(|c:char| c == '/' || c.is_whitespace()) {
+                        return Err(Self::invalid_opt(format!("Invalid hierarchy name = {}",n)));
+                    }
+                }
+
+                // without controller the kernel binds all of them, unless none is given
+                if controllers.is_empty() {
+                    if name.is_none() && self.opts.split(',').any(|opt| opt == "none") {
+                        return Err(Self::invalid_opt(
+                            String::from("Hierarchy without controller requires name=")
+                        ));
+                    }
+                    return Ok(());
+                }
+
+                let subsys = ProcCGroup::from_proc()?;
+                for ctrl in controllers {
+                    match subsys.iter().find(|s| s.subsys_name == ctrl) {
+                        Some(s) if s.enabled => (),
+                        Some(_) => return Err(Self::invalid_opt(format!("Controller disabled = {}",ctrl))),
+                        None => return Err(Self::invalid_opt(format!("Unknown controller = {}",ctrl))),
+                    }
+                }
+                Ok(())
+            }
+            "cgroup2" => {
+                for opt in self.opts.split(',').filter(|opt| !opt.is_empty()) {
+                    if CGroup2Option::parse(opt).is_none() {
+                        return Err(Self::invalid_opt(format!("Unknown cgroup2 option = {}",opt)));
+                    }
+                }
+                Ok(())
+            }
+            _ => Ok(())
+        }
+    }
+
+    fn invalid_opt(msg:String)->std::io::Error{
+        std::io::Error::new(std::io::ErrorKind::InvalidInput,msg)
+    }
+
     fn is_param_failed(s:&str)->bool{
         if s.trim().len() <= 0 { true } else { false }
     }
@@ -63,15 +272,37 @@ impl<'a,'b,'c,'d> MountBuilder<'a,'b,'c,'d>{
             Self::is_param_failed(self.type_name){
             return Err(std::io::Error::from(std::io::ErrorKind::InvalidData));
         }
+        self.validate()?;
 
-        unsafe {
-            std::fs::create_dir_all(self.target_path)?;
+        let created = !std::path::Path::new(self.target_path).exists();
+        std::fs::create_dir_all(self.target_path)?;
+
+        let ret = if self.fs_context && FsContext::is_mount_attr(self.flags) {
+            match self.mount_fs_context() {
+                Err(e) if FsError::errno_of(&e) == Some(libc::ENOSYS) => {
+                    info!("MountBuilder::mount[fs context unsupported] = fallback mount(2)");
+                    self.mount_legacy()
+                }
+                ret => ret
+            }
+        } else {
+            self.mount_legacy()
+        };
+
+        if ret.is_err() && created {
+            let _ = std::fs::remove_dir(self.target_path);
+        }
+        ret
+    }
 
+    /// mount(2)
+    fn mount_legacy(&self)->Result<(),std::io::Error>{
+        unsafe {
             let c_target_path = std::ffi::CString::new(self.target_path)?;
             let c_src_path = std::ffi::CString::new(self.src_path)?;
             let c_type_name = std::ffi::CString::new(self.type_name)?;
-            let c_flags = libc::c_ulong::from(self.flags);
-            let c_opts = std::ffi::CString::new(self.opts)?;
+            let c_flags = self.flags.bits();
+            let c_opts = std::ffi::CString::new(self.opts.as_ref())?;
             let c_opts_void = c_opts.as_ptr() as *const libc::c_void;
 
             let ret = libc::mount(
@@ -81,16 +312,32 @@ impl<'a,'b,'c,'d> MountBuilder<'a,'b,'c,'d>{
                 c_flags,
                 c_opts_void
             );
+            // errno must be read before logging may overwrite it
+            let e = std::io::Error::last_os_error();
             info!("MountBuilder::mount[return code] = {}",ret);
             if ret != 0 {
-                std::fs::remove_dir(self.target_path)?;
-                return Err(std::io::Error::from_raw_os_error(ret));
+                return Err(e);
             }
         }
 
         Ok(())
     }
 
+    /// fsopen(2) + fsconfig(2) + fsmount(2) + move_mount(2), since Linux 5.2
+    fn mount_fs_context(&self)->Result<(),std::io::Error>{
+        let ctx = FsContext::open(self.type_name)?;
+        ctx.set_string("source",self.src_path)?;
+        for opt in self.opts.split(',').filter(|opt| !opt.is_empty()) {
+            match opt.split_once('=') {
+                Some((key,value)) => ctx.set_string(key,value)?,
+                None => ctx.set_flag(opt)?,
+            }
+        }
+        ctx.create()?;
+        ctx.mount(FsContext::mount_attr(self.flags),self.target_path)
+    }
+
+    /// Target directory exists, it may still be an empty directory without hierarchy
     pub fn exists(&self)->bool{
         match std::fs::metadata(self.target_path) {
             Ok(d) if !d.is_dir() => false,
@@ -101,27 +348,662 @@ impl<'a,'b,'c,'d> MountBuilder<'a,'b,'c,'d>{
 
 
 
-    pub fn umount(&self,flag:i32)->Result<(),std::io::Error>{
+    pub fn umount(&self,flags:UmountFlags)->Result<(),std::io::Error>{
         if Self::is_param_failed(self.target_path) {
             return Err(std::io::Error::from(std::io::ErrorKind::InvalidData));
         }
 
         unsafe {
             let c_target_path = std::ffi::CString::new(self.target_path)?;
-            let ret = if flag == MNT_DEFAULT {
+            let ret = if flags == UmountFlags::NONE {
                 libc::umount(c_target_path.as_ptr())
             }else{
-                let c_flags = libc::c_int::from(flag);
-                libc::umount2(c_target_path.as_ptr(),c_flags)
+                libc::umount2(c_target_path.as_ptr(),flags.bits())
             };
+            let e = std::io::Error::last_os_error();
 
             info!("MountBuilder::umount[return code] = {}",ret);
             if ret != 0 {
-                return Err(std::io::Error::from_raw_os_error(ret))
+                return Err(e)
             }
             std::fs::remove_dir(self.target_path)?;
         }
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Target directory is the mount point of a cgroup/cgroup2 hierarchy(/proc/self/mountinfo)
+    pub fn is_mounted(&self)->Result<bool,std::io::Error>{
+        if Self::is_param_failed(self.target_path) {
+            return Err(std::io::Error::from(std::io::ErrorKind::InvalidData));
+        }
+
+        let target = match std::fs::canonicalize(self.target_path) {
+            Ok(target) => target,
+            Err(e) if e.kind().eq(&std::io::ErrorKind::NotFound) => return Ok(false),
+            Err(e) => return Err(e),
+        };
+
+        Ok(CGroupMount::from_self()?
+            .iter()
+            .any(|m| std::path::Path::new(&m.mount_point).eq(target.as_path())))
+    }
+}
+
+
+
+const FSOPEN_CLOEXEC:libc::c_uint = 0x1;
+const FSCONFIG_SET_FLAG:libc::c_uint = 0;
+const FSCONFIG_SET_STRING:libc::c_uint = 1;
+const FSCONFIG_CMD_CREATE:libc::c_uint = 6;
+const FSMOUNT_CLOEXEC:libc::c_uint = 0x1;
+const MOVE_MOUNT_F_EMPTY_PATH:libc::c_uint = 0x4;
+
+const MOUNT_ATTR_RDONLY:libc::c_uint = 0x1;
+const MOUNT_ATTR_NOSUID:libc::c_uint = 0x2;
+const MOUNT_ATTR_NODEV:libc::c_uint = 0x4;
+const MOUNT_ATTR_NOEXEC:libc::c_uint = 0x8;
+const MOUNT_ATTR_NOATIME:libc::c_uint = 0x10;
+const MOUNT_ATTR_STRICTATIME:libc::c_uint = 0x20;
+const MOUNT_ATTR_NODIRATIME:libc::c_uint = 0x80;
+
+
+/// Failure of the new mount API, the errno with the messages the kernel queued in the context
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct FsError{
+    pub errno:i32,
+    pub log:Vec<String>,
+}
+
+impl FsError{
+    /// errno of an error of `MountBuilder::mount`, plain or carrying a FsError
+    pub fn errno_of(e:&std::io::Error)->Option<i32>{
+        e.raw_os_error().or_else(|| {
+            e.get_ref()
+                .and_then(|inner| inner.downcast_ref::<FsError>())
+                .map(|fs| fs.errno)
+        })
+    }
+}
+
+impl std::fmt::Display for FsError{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f,"{}",std::io::Error::from_raw_os_error(self.errno))?;
+        for message in &self.log {
+            write!(f,"; {}",message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for FsError {}
+
+
+/// Filesystem context of the new mount API, the descriptor is closed on drop
+struct FsContext{
+    fd:libc::c_int,
+}
+
+impl FsContext{
+
+    /// Flags which have a MOUNT_ATTR_* equivalent
+    const ATTR_FLAGS:[(MountFlags,libc::c_uint);7] = [
+        (MountFlags::RDONLY,MOUNT_ATTR_RDONLY),
+        (MountFlags::NOSUID,MOUNT_ATTR_NOSUID),
+        (MountFlags::NODEV,MOUNT_ATTR_NODEV),
+        (MountFlags::NOEXEC,MOUNT_ATTR_NOEXEC),
+        (MountFlags::NOATIME,MOUNT_ATTR_NOATIME),
+        (MountFlags::STRICTATIME,MOUNT_ATTR_STRICTATIME),
+        (MountFlags::NODIRATIME,MOUNT_ATTR_NODIRATIME),
+    ];
+
+    fn is_mount_attr(flags:MountFlags)->bool{
+        let known = Self::ATTR_FLAGS
+            .iter()
+            .fold(MountFlags::RELATIME.bits(),|acc,(f,_)| acc | f.bits());
+        flags.bits() & !known == 0
+    }
+
+    fn mount_attr(flags:MountFlags)->libc::c_uint{
+        Self::ATTR_FLAGS
+            .iter()
+            .filter(|(f,_)| flags.contains(*f))
+            .fold(0,|acc,(_,attr)| acc | attr)
+    }
+
+    fn open(fs_name:&str)->Result<Self,std::io::Error>{
+        let c_fs_name = std::ffi::CString::new(fs_name)?;
+        let fd = unsafe {
+            libc::syscall(libc::SYS_fsopen,c_fs_name.as_ptr(),FSOPEN_CLOEXEC)
+        };
+        let e = std::io::Error::last_os_error();
+        info!("FsContext::open[return code] = {}",fd);
+        if fd < 0 {
+            return Err(e);
+        }
+        Ok(Self{ fd: fd as libc::c_int })
+    }
+
+    fn set_flag(&self,key:&str)->Result<(),std::io::Error>{
+        let c_key = std::ffi::CString::new(key)?;
+        let ret = unsafe {
+            libc::syscall(
+                libc::SYS_fsconfig,
+                self.fd,
+                FSCONFIG_SET_FLAG,
+                c_key.as_ptr(),
+                std::ptr::null::<libc::c_char>(),
+                0
+            )
+        };
+        self.check(ret,key)
+    }
+
+    fn set_string(&self,key:&str,value:&str)->Result<(),std::io::Error>{
+        let c_key = std::ffi::CString::new(key)?;
+        let c_value = std::ffi::CString::new(value)?;
+        let ret = unsafe {
+            libc::syscall(
+                libc::SYS_fsconfig,
+                self.fd,
+                FSCONFIG_SET_STRING,
+                c_key.as_ptr(),
+                c_value.as_ptr(),
+                0
+            )
+        };
+        self.check(ret,key)
+    }
+
+    fn create(&self)->Result<(),std::io::Error>{
+        let ret = unsafe {
+            libc::syscall(
+                libc::SYS_fsconfig,
+                self.fd,
+                FSCONFIG_CMD_CREATE,
+                std::ptr::null::<libc::c_char>(),
+                std::ptr::null::<libc::c_char>(),
+                0
+            )
+        };
+        self.check(ret,"create")
+    }
+
+    fn mount(&self,attr:libc::c_uint,target_path:&str)->Result<(),std::io::Error>{
+        let c_target_path = std::ffi::CString::new(target_path)?;
+        unsafe {
+            let mnt_fd = libc::syscall(libc::SYS_fsmount,self.fd,FSMOUNT_CLOEXEC,attr);
+            info!("FsContext::fsmount[return code] = {}",mnt_fd);
+            if mnt_fd < 0 {
+                return self.check(mnt_fd,"fsmount");
+            }
+
+            let c_empty = std::ffi::CString::new("")?;
+            let ret = libc::syscall(
+                libc::SYS_move_mount,
+                mnt_fd as libc::c_int,
+                c_empty.as_ptr(),
+                libc::AT_FDCWD,
+                c_target_path.as_ptr(),
+                MOVE_MOUNT_F_EMPTY_PATH
+            );
+            // errno must be read before close overwrites it
+            let e = std::io::Error::last_os_error();
+            libc::close(mnt_fd as libc::c_int);
+            info!("FsContext::move_mount[return code] = {}",ret);
+            if ret < 0 {
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Error with the errno and the kernel messages of the context, see `FsError::errno_of`
+    fn check(&self,ret:libc::c_long,step:&str)->Result<(),std::io::Error>{
+        let e = std::io::Error::last_os_error();
+        info!("FsContext::{}[return code] = {}",step,ret);
+        if ret >= 0 {
+            return Ok(());
+        }
+
+        let log = self.read_log();
+        for message in &log {
+            error!("FsContext::{}[kernel log] = {}",step,message);
+        }
+        let errno = e.raw_os_error().unwrap_or(libc::EIO);
+        Err(std::io::Error::new(e.kind(),FsError{ errno, log }))
+    }
+
+    /// Messages queued in the context(`e ...`, `w ...`, `i ...`), read until ENODATA
+    fn read_log(&self)->Vec<String>{
+        let mut log = Vec::new();
+        let mut buf = [0u8;4096];
+        loop {
+            let n = unsafe {
+                libc::read(self.fd,buf.as_mut_ptr() as *mut libc::c_void,buf.len())
+            };
+            if n <= 0 {
+                break;
+            }
+            log.push(String::from_utf8_lossy(&buf[..n as usize]).trim_end().to_string());
+        }
+        log
+    }
+}
+
+impl Drop for FsContext{
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.fd);
+        }
+    }
+}
+
+
+
+/// Super options of cgroup v1 which are not controllers
+fn is_v1_generic_opt(opt:&str)->bool{
+    matches!(opt,"rw" | "ro" | "none" | "all" | "noprefix" | "clone_children" | "xattr" | "cpuset_v2_mode")
+        || opt.starts_with("release_agent=")
+}
+
+
+
+/// Mount options of cgroup2
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum CGroup2Option{
+    NsDelegate,
+    FavorDynMods,
+    MemoryLocalEvents,
+    MemoryRecursiveProt,
+    MemoryHugetlbAccounting,
+    PidsLocalEvents
+}
+
+impl CGroup2Option{
+    pub fn as_str(&self)->&'static str{
+        match self {
+            CGroup2Option::NsDelegate => "nsdelegate",
+            CGroup2Option::FavorDynMods => "favordynmods",
+            CGroup2Option::MemoryLocalEvents => "memory_localevents",
+            CGroup2Option::MemoryRecursiveProt => "memory_recursiveprot",
+            CGroup2Option::MemoryHugetlbAccounting => "memory_hugetlb_accounting",
+            CGroup2Option::PidsLocalEvents => "pids_localevents",
+        }
+    }
+
+    pub fn parse(opt:&str)->Option<Self>{
+        match opt {
+            "nsdelegate" => Some(CGroup2Option::NsDelegate),
+            "favordynmods" => Some(CGroup2Option::FavorDynMods),
+            "memory_localevents" => Some(CGroup2Option::MemoryLocalEvents),
+            "memory_recursiveprot" => Some(CGroup2Option::MemoryRecursiveProt),
+            "memory_hugetlb_accounting" => Some(CGroup2Option::MemoryHugetlbAccounting),
+            "pids_localevents" => Some(CGroup2Option::PidsLocalEvents),
+            _ => None
+        }
+    }
+}
+
+
+
+/// One line of /proc/cgroups
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct ProcCGroup{
+    pub subsys_name:String,
+    pub hierarchy:u32,
+    pub num_cgroups:u32,
+    pub enabled:bool,
+}
+
+impl ProcCGroup{
+
+    pub fn from_proc()->Result<Vec<Self>,std::io::Error>{
+        Self::parse(&std::fs::read_to_string(PROC_CGROUPS)?)
+    }
+
+    /// `#subsys_name hierarchy num_cgroups enabled`
+    pub fn parse(content:&str)->Result<Vec<Self>,std::io::Error>{
+        let mut subsys = Vec::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = || std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Invalid cgroups line = {}",line)
+            );
+            let fields:Vec<&str> = line.split_whitespace().collect();
+            if fields.len() != 4 {
+                return Err(invalid());
+            }
+            subsys.push(Self{
+                subsys_name:fields[0].to_string(),
+                hierarchy:fields[1].parse().map_err(|_| invalid())?,
+                num_cgroups:fields[2].parse().map_err(|_| invalid())?,
+                enabled:fields[3] == "1",
+            });
+        }
+        Ok(subsys)
+    }
+}
+
+
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum CGroupVersion{
+    V1,
+    V2
+}
+
+
+/// Optional fields of mountinfo(shared:X, master:X, propagate_from:X, unbindable)
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum Propagation{
+    Shared(u32),
+    Master(u32),
+    PropagateFrom(u32),
+    Unbindable
+}
+
+
+/// One cgroup/cgroup2 line of /proc/<pid>/mountinfo
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct CGroupMount{
+    pub mount_id:u32,
+    pub parent_id:u32,
+    pub major:u32,
+    pub minor:u32,
+    /// root of the mount within the hierarchy
+    pub root:String,
+    pub mount_point:String,
+    pub mount_opts:Vec<String>,
+    /// empty when the mount is private
+    pub propagation:Vec<Propagation>,
+    pub fs_type:String,
+    pub source:String,
+    pub super_opts:Vec<String>,
+    pub version:CGroupVersion,
+    /// controllers bound to a v1 hierarchy, always empty on cgroup2
+    pub controllers:Vec<String>,
+    /// name= option of a v1 named hierarchy
+    pub name:Option<String>,
+}
+
+impl CGroupMount{
+
+    /// Mounts of the current process(/proc/self/mountinfo)
+    pub fn from_self()->Result<Vec<Self>,std::io::Error>{
+        Self::from_mountinfo(PROC_SELF_MOUNTINFO)
+    }
+
+    pub fn from_mountinfo(pathname:&str)->Result<Vec<Self>,std::io::Error>{
+        Self::parse(&std::fs::read_to_string(pathname)?)
+    }
+
+    /// Keep only cgroup/cgroup2 mounts of a mountinfo content
+    pub fn parse(content:&str)->Result<Vec<Self>,std::io::Error>{
+        let mut mounts = Vec::new();
+        for line in content.lines() {
+            if let Some(m) = Self::parse_line(line)? {
+                mounts.push(m);
+            }
+        }
+        Ok(mounts)
+    }
+
+    /// Parse one mountinfo line, `None` when the filesystem is not cgroup/cgroup2
+    ///
+    /// `36 35 98:0 /mnt1 /mnt2 rw,noatime master:1 - ext3 /dev/root rw,errors=continue`
+    pub fn parse_line(line:&str)->Result<Option<Self>,std::io::Error>{
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(None);
+        }
+
+        let (head,tail) = match line.find(" - ") {
+            Some(idx) => (&line[..idx],&line[idx + 3..]),
+            None => return Err(Self::invalid(line)),
+        };
+
+        let mut tail_fields = tail.split_whitespace();
+        let fs_type = tail_fields.next().ok_or_else(|| Self::invalid(line))?;
+        let version = match fs_type {
+            "cgroup" => CGroupVersion::V1,
+            "cgroup2" => CGroupVersion::V2,
+            _ => return Ok(None),
+        };
+        let source = Self::unescape(tail_fields.next().ok_or_else(|| Self::invalid(line))?);
+        let super_opts:Vec<String> = tail_fields
+            .next()
+            .unwrap_or("")
+            .split(',')
+            .filter(|opt| !opt.is_empty())
+            .map(String::from)
+            .collect();
+
+        let mut fields = head.split_whitespace();
+        let mut next_field = || fields.next().ok_or_else(|| Self::invalid(line));
+        let mount_id = Self::parse_num(next_field()?,line)?;
+        let parent_id = Self::parse_num(next_field()?,line)?;
+        let (major,minor) = match next_field()?.split_once(':') {
+            Some((major,minor)) => (Self::parse_num(major,line)?,Self::parse_num(minor,line)?),
+            None => return Err(Self::invalid(line)),
+        };
+        let root = Self::unescape(next_field()?);
+        let mount_point = Self::unescape(next_field()?);
+        let mount_opts = next_field()?.split(',').map(String::from).collect();
+
+        let mut propagation = Vec::new();
+        for tag in fields {
+            let p = match tag.split_once(':') {
+                Some(("shared",id)) => Propagation::Shared(Self::parse_num(id,line)?),
+                Some(("master",id)) => Propagation::Master(Self::parse_num(id,line)?),
+                Some(("propagate_from",id)) => Propagation::PropagateFrom(Self::parse_num(id,line)?),
+                None if tag == "unbindable" => Propagation::Unbindable,
+                _ => continue,
+            };
+            propagation.push(p);
+        }
+
+        let mut controllers = Vec::new();
+        let mut name = None;
+        if version == CGroupVersion::V1 {
+            for opt in super_opts.iter() {
+                if let Some(n) = opt.strip_prefix("name=") {
+                    name = Some(n.to_string());
+                } else if !is_v1_generic_opt(opt) {
+                    controllers.push(opt.clone());
+                }
+            }
+        }
+
+        Ok(Some(Self{
+            mount_id,
+            parent_id,
+            major,
+            minor,
+            root,
+            mount_point,
+            mount_opts,
+            propagation,
+            fs_type:fs_type.to_string(),
+            source,
+            super_opts,
+            version,
+            controllers,
+            name
+        }))
+    }
+
+    pub fn has_controller(&self,ctrl_name:&str)->bool{
+        self.controllers.iter().any(|c| c == ctrl_name)
+    }
+
+    pub fn is_shared(&self)->bool{
+        self.propagation.iter().any(|p| matches!(p,Propagation::Shared(_)))
+    }
+
+    /// Octal escapes used by the kernel for space, tab, newline and backslash
+    fn unescape(s:&str)->String{
+        let bytes = s.as_bytes();
+        let mut out = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == b'\\'
+                && i + 4 <= bytes.len()
+                && bytes[i + 1..i + 4].iter().all(|b| (b'0'..=b'7').contains(b)) {
+                let c = bytes[i + 1..i + 4]
+                    .iter()
+                    .fold(0u32,|acc,b| acc * 8 + u32::from(b - b'0'));
+                out.push(c as u8);
+                i += 4;
+                continue;
+            }
+            out.push(bytes[i]);
+            i += 1;
+        }
+        String::from_utf8_lossy(&out).into_owned()
+    }
+
+    fn parse_num(s:&str,line:&str)->Result<u32,std::io::Error>{
+        s.parse::<u32>().map_err(|_| Self::invalid(line))
+    }
+
+    fn invalid(line:&str)->std::io::Error{
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Invalid mountinfo line = {}",line)
+        )
+    }
+}
+
+
+
+/// One line of /proc/<pid>/cgroup(`hierarchy-ID:controller-list:cgroup-path`)
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct ProcessCGroup{
+    /// 0 for the unified(cgroup2) hierarchy
+    pub hierarchy:u32,
+    /// controllers of a v1 hierarchy, empty on cgroup2 and named hierarchies
+    pub controllers:Vec<String>,
+    /// name= of a v1 named hierarchy
+    pub name:Option<String>,
+    /// path relative to the root of the hierarchy
+    pub path:String,
+}
+
+impl ProcessCGroup{
+    pub fn is_unified(&self)->bool{
+        self.hierarchy == 0 && self.controllers.is_empty() && self.name.is_none()
+    }
+}
+
+
+/// Groups of a process read from /proc/<pid>/cgroup
+#[derive(Debug,Clone,PartialEq,Eq,Default)]
+pub struct ProcessCGroups{
+    pub entries:Vec<ProcessCGroup>,
+}
+
+impl ProcessCGroups{
+
+    pub fn for_pid(pid:i32)->Result<Self,std::io::Error>{
+        Self::parse(&std::fs::read_to_string(format!("/proc/{}/cgroup",pid))?)
+    }
+
+    pub fn from_self()->Result<Self,std::io::Error>{
+        Self::parse(&std::fs::read_to_string("/proc/self/cgroup")?)
+    }
+
+    /// `4:cpu,cpuacct:/user.slice`, `1:name=systemd:/init.scope`, `0::/init.scope`
+    pub fn parse(content:&str)->Result<Self,std::io::Error>{
+        let mut entries = Vec::new();
+        for line in content.lines() {
+            let line = line.trim_end();
+            if line.is_empty() {
+                continue;
+            }
+
+            let invalid = || std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Invalid cgroup line = {}",line)
+            );
+            // the path may contain ':'
+            let mut fields = line.splitn(3,':');
+            let hierarchy = fields.next().and_then(|h| h.parse::<u32>().ok()).ok_or_else(invalid)?;
+            let list = fields.next().ok_or_else(invalid)?;
+            let path = fields.next().ok_or_else(invalid)?;
+
+            let mut controllers = Vec::new();
+            let mut name = None;
+            for ctrl in list.split(',').filter(|c| !c.is_empty()) {
+                match ctrl.strip_prefix("name=") {
+                    Some(n) => name = Some(n.to_string()),
+                    None => controllers.push(ctrl.to_string()),
+                }
+            }
+            entries.push(ProcessCGroup{
+                hierarchy,
+                controllers,
+                name,
+                path:path.to_string(),
+            });
+        }
+        Ok(Self{ entries })
+    }
+
+    /// Path of the cgroup2 hierarchy(`0::/path`)
+    pub fn unified(&self)->Option<&str>{
+        self.entries.iter().find(|e| e.is_unified()).map(|e| e.path.as_str())
+    }
+
+    /// Relative path of a controller or of a named hierarchy(`name=systemd`),
+    /// controllers missing from v1 hierarchies fall back to the cgroup2 path
+    pub fn get_path(&self,ctrl_name:&str)->Option<&str>{
+        let v1 = match ctrl_name.strip_prefix("name=") {
+            Some(n) => self.entries.iter().find(|e| e.name.as_deref() == Some(n)),
+            None => self.entries.iter().find(|e| e.controllers.iter().any(|c| c == ctrl_name)),
+        };
+        match v1 {
+            Some(e) => Some(e.path.as_str()),
+            None if !ctrl_name.starts_with("name=") => self.unified(),
+            None => None,
+        }
+    }
+
+    /// Controller(or `name=` hierarchy) to relative path
+    pub fn paths(&self)->std::collections::BTreeMap<String,String>{
+        let mut paths = std::collections::BTreeMap::new();
+        for e in self.entries.iter() {
+            for ctrl in e.controllers.iter() {
+                paths.insert(ctrl.clone(),e.path.clone());
+            }
+            if let Some(n) = &e.name {
+                paths.insert(format!("name={}",n),e.path.clone());
+            }
+        }
+        paths
+    }
+
+    /// Absolute path of a controller, joined to the mount point found by `CGroupControllerIterator`
+    pub fn resolve(&self,ctrl_name:&str)->Result<Option<std::path::PathBuf>,std::io::Error>{
+        Ok(self.resolve_all()?.remove(ctrl_name))
+    }
+
+    /// Absolute paths of every mounted controller the process belongs to
+    pub fn resolve_all(&self)->Result<std::collections::BTreeMap<String,std::path::PathBuf>,std::io::Error>{
+        let mut paths = std::collections::BTreeMap::new();
+        for mount in CGroupControllerIterator::from()? {
+            let ctrl_name = mount.get_name();
+            if let Some(path) = self.get_path(&ctrl_name) {
+                let full_path = std::path::Path::new(&mount.get_path())
+                    .join(path.trim_start_matches('/'));
+                paths.entry(ctrl_name).or_insert(full_path);
+            }
+        }
+        Ok(paths)
+    }
+}
@@ -5,6 +5,7 @@
 //! Functions:
 //!    - cgroup_init
 //!    - cgroup_get_subsys_mount_point
+//!    - cgroup_get_subsys_mount_point_begin
 //!
 //! Usage:
 //! ```
@@ -23,14 +24,9 @@
 //!             .eq(&cg_get_error(C_EC_GROUP_NOT_MOUNTED).kind()) => {
 //!
 //!             // Mouth Space
-//!             let mut space = MountBuilder::new();
-//!             space
-//!                 .set_target_path("/dev/shm/cgroups")
-//!                 .set_src_path("cgroup")
-//!                 .set_type_name("cgroup")
-//!                 .set_opts("cpu");
+//!             let space = MountBuilder::cgroup_v1("/dev/shm/cgroups",&["cpu"]);
 //!
-//!             if !space.exists() {
+//!             if !space.is_mounted()? {
 //!                 space.mount()?;
 //!             }
 //!             CGroupInitializer::init()?;
@@ -39,6 +35,7 @@
 //!     }
 //!
 //!     println!("Mount Point = {:?}",CGroupInitializer::get_subsys_mount_point("cpu"));
+//!     println!("Mount Points = {:?}",CGroupInitializer::get_subsys_mount_points("cpu")?);
 //!
 //!     Ok(())
 //! }
@@ -48,6 +45,7 @@
 
 use crate::prelude::*;
 use crate::error::*;
+use crate::iterators::CGroupSubsysMountPointIterator;
 use log::{info};
 
 pub struct CGroupInitializer;
@@ -66,25 +64,35 @@ impl CGroupInitializer{
     }
 
 
+    /// First mount point of the controller(cgroup_get_subsys_mount_point)
+    ///
+    /// The path returned by libcgroup is copied and released,
+    /// use `get_subsys_mount_points` when the controller is mounted in several places.
     pub fn get_subsys_mount_point(ctrl_name:&str)->Option<String>{
         unsafe {
             if let Ok(c_ctrl_name) = std::ffi::CString::new(ctrl_name) {
-                let c_point = std::ptr::null();
+                let mut c_point: *mut libc::c_char = std::ptr::null_mut();
                 let ret = cgroup_get_subsys_mount_point(
                     c_ctrl_name.as_ptr(),
-                    &c_point as *const *const libc::c_char
+                    &mut c_point
                 );
                 info!("CGROUP::get_subsys_mount_point[return code] = {}",ret);
-                if ret == C_GROUP_SUCCESS {
-                    return Some(std::ffi::CStr::from_ptr(c_point)
+                if ret == C_GROUP_SUCCESS && !c_point.is_null() {
+                    let point = std::ffi::CStr::from_ptr(c_point)
                         .to_string_lossy()
-                        .to_owned()
-                        .to_string())
+                        .into_owned();
+                    libc::free(c_point as *mut libc::c_void);
+                    return Some(point)
                 }
 
             }
         }
-        return None;
+        None
+    }
+
+    /// Every mount point of the controller(cgroup_get_subsys_mount_point_begin)
+    pub fn get_subsys_mount_points(ctrl_name:&str)->Result<Vec<String>,std::io::Error>{
+        Ok(CGroupSubsysMountPointIterator::from(ctrl_name)?.collect())
     }
 
 }
\ No newline at end of file
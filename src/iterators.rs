@@ -20,6 +20,40 @@
 //!    - cgroup_get_all_controller_begin
 //!    - cgroup_get_all_controller_next
 //!    - cgroup_get_all_controller_end
+//!    - cgroup_get_subsys_mount_point_begin
+//!    - cgroup_get_subsys_mount_point_next
+//!    - cgroup_get_subsys_mount_point_end
+//!
+//! Usage(Tasks of a subtree):
+//! ```
+//! use libcgroup_rs::initialization::CGroupInitializer;
+//! use libcgroup_rs::iterators::{CGroupWalkIterator, TaskKind};
+//!
+//! fn main()->Result<(),Box<dyn std::error::Error>>{
+//!     CGroupInitializer::init()?;
+//!
+//!     for (path,pid) in CGroupWalkIterator::from("cpu","jobs",0)?.tasks(TaskKind::Procs)? {
+//!         println!("{} = {}",path,pid);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+//!
+//! Usage(Procs):
+//! ```
+//! use libcgroup_rs::initialization::CGroupInitializer;
+//! use libcgroup_rs::iterators::CGroupProcsIterator;
+//!
+//! fn main()->Result<(),Box<dyn std::error::Error>>{
+//!     CGroupInitializer::init()?;
+//!
+//!     // thread-group ids only, `CGroupTaskIterator` returns every thread
+//!     for pid in CGroupProcsIterator::from("foo","cpu")? {
+//!         println!("Pid = {}",pid);
+//!     }
+//!     Ok(())
+//! }
+//! ```
 //!
 //! Usage(All_Controller):
 //! ```
@@ -98,22 +132,236 @@
 //! }
 //! ```
 //!
+//! Usage(Mount Point):
+//! ```
+//! use libcgroup_rs::initialization::CGroupInitializer;
+//! use libcgroup_rs::iterators::CGroupSubsysMountPointIterator;
+//!
+//! fn main()->Result<(),Box<dyn std::error::Error>>{
+//!     CGroupInitializer::init()?;
+//!
+//!     let mount_iter = CGroupSubsysMountPointIterator::from("cpu")?;
+//!     for path in mount_iter.into_iter() {
+//!         println!("Path = {}",path);
+//!         println!("--------------------------------------------");
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
 
 
 use crate::prelude::*;
 use crate::error::*;
+use crate::initialization::CGroupInitializer;
 
 use log::{info};
 
 
+/// Ids listed by a group
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum TaskKind{
+    /// thread-group ids(cgroup.procs)
+    Procs,
+    /// every thread(tasks)
+    Threads,
+}
+
+
+/// When directories are returned by the walk(CGROUP_WALK_TYPE_*)
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+pub enum WalkOrder{
+    /// parents before children(CGROUP_WALK_TYPE_PRE_DIR)
+    #[default]
+    Pre,
+    /// children before parents, the order to remove a tree(CGROUP_WALK_TYPE_POST_DIR)
+    Post,
+}
+
+impl WalkOrder{
+    pub fn bits(&self)->libc::c_int{
+        match self {
+            WalkOrder::Pre => 0x1,
+            WalkOrder::Post => 0x2,
+        }
+    }
+}
+
+
+/// How deep the walk goes below the base group
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+pub enum WalkDepth{
+    #[default]
+    Unbounded,
+    /// levels below the base group, `Limited(1)` is the base group and its children,
+    /// like libcgroup a limit of 0 is no limit
+    Limited(u16),
+}
+
+impl WalkDepth{
+    /// depth of cgroup_walk_tree_begin
+    pub fn as_raw(&self)->libc::c_int{
+        match self {
+            WalkDepth::Unbounded => 0,
+            WalkDepth::Limited(depth) => libc::c_int::from(*depth),
+        }
+    }
+
+    pub fn from_raw(depth:libc::c_int)->Self{
+        use std::convert::TryFrom;
+        match u16::try_from(depth) {
+            Ok(depth) if depth > 0 => WalkDepth::Limited(depth),
+            _ => WalkDepth::Unbounded,
+        }
+    }
+}
+
+
+/// Options of a walk
+///
+/// ```
+/// use libcgroup_rs::initialization::CGroupInitializer;
+/// use libcgroup_rs::iterators::{CGroupWalkBuilder, WalkDepth, WalkOrder};
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>>{
+///     CGroupInitializer::init()?;
+///
+///     let walk = CGroupWalkBuilder::new("cpu","jobs")
+///         .set_order(WalkOrder::Post)
+///         .set_depth(WalkDepth::Limited(2))
+///         .build()?;
+///     for group in walk.groups()? {
+///         println!("Group = {}",group);
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub struct CGroupWalkBuilder<'a>{
+    ctrl_name:&'a str,
+    base_path:&'a str,
+    order:WalkOrder,
+    depth:WalkDepth,
+}
+
+impl<'a> CGroupWalkBuilder<'a>{
+
+    pub fn new(ctrl_name:&'a str,base_path:&'a str)->Self{
+        Self{
+            ctrl_name,
+            base_path,
+            order:WalkOrder::Pre,
+            depth:WalkDepth::Unbounded,
+        }
+    }
+
+    pub fn set_order(&mut self,order:WalkOrder)->&mut Self{
+        self.order = order;
+        self
+    }
+
+    pub fn set_depth(&mut self,depth:WalkDepth)->&mut Self{
+        self.depth = depth;
+        self
+    }
+
+    pub fn get_ctrl_name(&self)->&'a str{
+        self.ctrl_name
+    }
+
+    pub fn get_base_path(&self)->&'a str{
+        self.base_path
+    }
+
+    pub fn get_order(&self)->WalkOrder{
+        self.order
+    }
+
+    pub fn get_depth(&self)->WalkDepth{
+        self.depth
+    }
+
+    pub fn build(&self)->Result<CGroupWalkIterator,std::io::Error>{
+        let mut walk = CGroupWalkIterator::from(self.ctrl_name,self.base_path,self.depth.as_raw())?;
+        if self.order != WalkOrder::Pre {
+            walk.set_flags(self.order)?;
+            // cgroup_walk_tree_begin already visited the base group in pre-order,
+            // it comes again after its children
+            if matches!(walk.info.c_type,cgroup_file_type::DIR) {
+                walk.cg_next();
+            }
+        }
+        Ok(walk)
+    }
+}
+
+
+/// Type of a walked file(cgroup_file_type)
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum WalkEntryKind{
+    File,
+    Dir,
+    Other,
+}
+
+
+/// Owned copy of a walked entry(struct cgroup_file_info)
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct WalkEntry{
+    pub kind:WalkEntryKind,
+    /// name of the file
+    pub path:std::path::PathBuf,
+    /// name of the parent directory
+    pub parent:std::path::PathBuf,
+    pub full_path:std::path::PathBuf,
+    /// fts level relative to the base group of the walk, 0 for the base group itself
+    pub depth:i32,
+    /// last level of the walk, 0 when the depth is unbounded
+    pub base_level:i32,
+}
+
+impl WalkEntry{
+
+    /// Copy the strings out of libcgroup's buffers, which are reused by the next step of the walk
+    fn from_info(info:&CGroupFileInfo,base_level:libc::c_int)->Self{
+        let to_path = |ptr:*const libc::c_char| {
+            if ptr.is_null() {
+                return std::path::PathBuf::new();
+            }
+            let bytes = unsafe { std::ffi::CStr::from_ptr(ptr) }.to_bytes();
+            std::path::PathBuf::from(<std::ffi::OsStr as std::os::unix::ffi::OsStrExt>::from_bytes(bytes))
+        };
+        Self{
+            kind:match info.c_type {
+                cgroup_file_type::FILE => WalkEntryKind::File,
+                cgroup_file_type::DIR => WalkEntryKind::Dir,
+                cgroup_file_type::OTHER => WalkEntryKind::Other,
+            },
+            path:to_path(info.path),
+            parent:to_path(info.parent),
+            full_path:to_path(info.full_path),
+            depth:i32::from(info.depth),
+            base_level,
+        }
+    }
+
+    pub fn is_dir(&self)->bool{
+        self.kind == WalkEntryKind::Dir
+    }
+}
+
+
 pub struct CGroupWalkIterator {
-    ctrl_name: *const libc::c_char,
-    base_path: *const libc::c_char,
+    ctrl_name: std::ffi::CString,
+    base_path: std::ffi::CString,
     depth: libc::c_int,
     handler: *const libc::c_void,
     info: CGroupFileInfo,
     base_level: libc::c_int,
     ret: libc::c_int,
+    /// the walk handle was released
+    ended: bool,
 }
 
 
@@ -121,13 +369,14 @@ impl CGroupWalkIterator {
 
     pub fn from(ctrl_name:&str,base_path:&str,depth:i32)->Result<Self,std::io::Error> {
         let mut handler = Self {
-            ctrl_name: std::ffi::CString::new(ctrl_name)?.as_ptr(),
-            base_path: std::ffi::CString::new(base_path)?.as_ptr(),
+            ctrl_name: std::ffi::CString::new(ctrl_name)?,
+            base_path: std::ffi::CString::new(base_path)?,
             depth: libc::c_int::from(depth),
             handler: std::ptr::null(),
             info: CGroupFileInfo::default(),
             base_level: 0,
-            ret: 0
+            ret: 0,
+            ended: false
         };
 
 
@@ -138,14 +387,14 @@ impl CGroupWalkIterator {
         Ok(handler)
     }
 
-    pub fn cg_begin(&mut self)->Option<(i32,CGroupFileInfo)>{
+    pub fn cg_begin(&mut self)->Option<WalkEntry>{
         unsafe {
             let c_handler = &(self.handler) as *const *const libc::c_void;
             let c_info = &mut self.info;
             let c_base_level = &mut self.base_level;
             self.ret = cgroup_walk_tree_begin(
-                self.ctrl_name,
-                self.base_path,
+                self.ctrl_name.as_ptr(),
+                self.base_path.as_ptr(),
                 self.depth,
                 c_handler,
                 c_info,
@@ -153,60 +402,126 @@ impl CGroupWalkIterator {
             );
             info!("CGroupWalkIterator::cg_begin[return code] = {}", self.ret);
             if self.ret == C_GROUP_SUCCESS {
-                let base_level = self.base_level as i32;
-                return Some((base_level,self.info.clone()));
+                return Some(WalkEntry::from_info(&self.info,self.base_level));
             }
         }
         None
     }
 
-    pub fn cg_next(&mut self)->Option<(i32,CGroupFileInfo)>{
+    pub fn cg_next(&mut self)->Option<WalkEntry>{
         unsafe {
             let c_handler = &(self.handler) as *const *const libc::c_void;
             let c_info = &mut self.info;
-            let c_base_level = &mut self.base_level;
-            self.ret = cgroup_walk_tree_next(c_handler,c_info,c_base_level);
+            self.ret = cgroup_walk_tree_next(self.depth,c_handler,c_info,self.base_level);
 
             info!("CGroupWalkIterator::cg_next[return code] = {}", self.ret);
             if self.ret == C_GROUP_SUCCESS {
-                let base_level = self.base_level as i32;
-                return Some((base_level,self.info.clone()));
+                return Some(WalkEntry::from_info(&self.info,self.base_level));
             }
         }
         None
     }
 
+    /// Release the walk handle, only the first call reaches libcgroup
     pub fn cg_end(&mut self) {
+        if self.ended || self.handler.is_null() {
+            return;
+        }
+        self.ended = true;
         unsafe {
             let c_handler = &(self.handler) as *const *const libc::c_void;
-            self.ret = cgroup_walk_tree_end(c_handler);
-            info!("CGroupWalkIterator::cg_end[return code] = {}", self.ret);
+            let ret = cgroup_walk_tree_end(c_handler);
+            info!("CGroupWalkIterator::cg_end[return code] = {}", ret);
+        }
+    }
+
+    /// Paths of the groups of the walk relative to the root of the hierarchy, in walk order
+    pub fn groups(self)->Result<Vec<String>,std::io::Error>{
+        Ok(self.group_paths()?.into_iter().map(|(relative,_)| relative).collect())
+    }
+
+    /// `(relative path, full path)` of every directory of the walk
+    fn group_paths(self)->Result<Vec<(String,String)>,std::io::Error>{
+        let ctrl_name = self.ctrl_name.to_string_lossy().into_owned();
+        let mount_point = CGroupInitializer::get_subsys_mount_point(&ctrl_name)
+            .ok_or_else(|| cg_get_error(C_EC_GROUP_SUBSYS_NOT_MOUNTED))?;
+        let mount_point = mount_point.trim_end_matches('/');
+
+        let mut groups:Vec<(String,String)> = Vec::new();
+        for entry in self.filter(|entry| entry.is_dir()) {
+            let full_path = entry.full_path.to_string_lossy();
+            let full_path = full_path.trim_end_matches('/').to_string();
+            let relative = match full_path.strip_prefix(mount_point) {
+                Some("") => String::from("/"),
+                Some(relative) => relative.to_string(),
+                None => full_path.clone(),
+            };
+            groups.push((relative,full_path));
+        }
+        Ok(groups)
+    }
+
+    /// Pids of every group of the walk as `(relative path, pid)`, groups in walk order
+    pub fn tasks(self,kind:TaskKind)->Result<Vec<(String,i32)>,std::io::Error>{
+        let ctrl_name = self.ctrl_name.to_string_lossy().into_owned();
+        let mut tasks = Vec::new();
+        for (relative,full_path) in self.group_paths()? {
+            let pids:Vec<i32> = match kind {
+                TaskKind::Procs => {
+                    match CGroupProcsIterator::from_path(format!("{}/cgroup.procs",full_path)) {
+                        Ok(procs) => procs.collect(),
+                        // removed during the walk
+                        Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                        Err(e) => return Err(e),
+                    }
+                }
+                TaskKind::Threads => {
+                    match CGroupTaskIterator::from(&relative,&ctrl_name) {
+                        Ok(threads) => threads.collect(),
+                        // removed during the walk, libcgroup only reports ECGOTHER or ECGROUPNOTEXIST
+                        Err(_) if !std::path::Path::new(&full_path).is_dir() => continue,
+                        Err(e) => return Err(e),
+                    }
+                }
+            };
+            tasks.extend(pids.into_iter().map(|pid| (relative.clone(),pid)));
         }
+        Ok(tasks)
     }
 
-    pub fn set_flags(&mut self,flags:i32)->i32{
+    /// Order of the next entries(cgroup_walk_tree_set_flags), see `CGroupWalkBuilder` to walk post-order from the start
+    pub fn set_flags(&mut self,order:WalkOrder)->Result<(),std::io::Error>{
         unsafe {
             let c_handler = &(self.handler) as *const *const libc::c_void;
-            let c_flags = libc::c_int::from(flags);
-            let ret = cgroup_walk_tree_set_flags(c_handler,c_flags);
+            let ret = cgroup_walk_tree_set_flags(c_handler,order.bits());
             info!("CGroupWalkIterator::set_flags[return code] = {}", ret);
-            ret
+            if ret != C_GROUP_SUCCESS {
+                return Err(cg_get_error(ret));
+            }
         }
+        Ok(())
+    }
+}
+
+impl Drop for CGroupWalkIterator{
+    /// Walks stopped before the last entry still release the handle
+    fn drop(&mut self) {
+        self.cg_end();
     }
 }
 
 impl Iterator for CGroupWalkIterator{
-    type Item = (i32,CGroupFileInfo);
+    type Item = WalkEntry;
 
     fn next(&mut self) -> Option<Self::Item> {
-        return if self.ret != 0 {
+        if self.ret != 0 {
             self.cg_end();
             None
         } else {
-            let info = self.info.clone();
-            let level = self.base_level as i32;
+            // copied before cg_next reuses the buffers
+            let entry = WalkEntry::from_info(&self.info,self.base_level);
             self.cg_next();
-            Some((level,info))
+            Some(entry)
         }
     }
 }
@@ -295,8 +610,8 @@ impl Iterator for CGroupStatsIterator{
 
 
 pub struct CGroupTaskIterator {
-    cg_name: *const libc::c_char,
-    ctrl_name: *const libc::c_char,
+    cg_name: std::ffi::CString,
+    ctrl_name: std::ffi::CString,
     handler: *const libc::c_void,
     pid: libc::pid_t,
     ret: libc::c_int,
@@ -306,15 +621,16 @@ impl CGroupTaskIterator {
 
     pub fn from(cg_name:&str,ctrl_name:&str)->Result<Self,std::io::Error>{
         let mut handler = Self{
-            cg_name: std::ffi::CString::new(cg_name)?.as_ptr(),
-            ctrl_name: std::ffi::CString::new(ctrl_name)?.as_ptr(),
+            cg_name: std::ffi::CString::new(cg_name)?,
+            ctrl_name: std::ffi::CString::new(ctrl_name)?,
             handler: std::ptr::null(),
             pid: 0,
             ret: 0
         };
 
         handler.cg_begin();
-        if handler.ret != C_GROUP_SUCCESS {
+        // an empty group ends right away
+        if handler.ret != C_GROUP_SUCCESS && handler.ret != C_EC_GROUP_EOF {
             return Err(cg_get_error(handler.ret));
         }
 
@@ -325,7 +641,7 @@ impl CGroupTaskIterator {
         unsafe {
             let c_handler = &(self.handler) as *const *const libc::c_void;
             let c_pid = &mut self.pid;
-            self.ret = cgroup_get_task_begin(self.cg_name,self.ctrl_name,c_handler, c_pid);
+            self.ret = cgroup_get_task_begin(self.cg_name.as_ptr(),self.ctrl_name.as_ptr(),c_handler, c_pid);
             info!("CGroupTaskIterator::cg_begin[return code] = {}", self.ret);
             if self.ret == C_GROUP_SUCCESS {
                 return Some(self.pid);
@@ -515,3 +831,136 @@ impl Iterator for CGroupAllControllerIterator{
 }
 
 
+
+
+pub struct CGroupSubsysMountPointIterator {
+    ctrl_name: std::ffi::CString,
+    handler: *const libc::c_void,
+    path: [libc::c_char; libc::FILENAME_MAX as usize],
+    ret: libc::c_int,
+}
+
+impl CGroupSubsysMountPointIterator {
+
+    pub fn from(ctrl_name:&str)->Result<Self,std::io::Error>{
+        let mut handler = Self {
+            ctrl_name: std::ffi::CString::new(ctrl_name)?,
+            handler: std::ptr::null(),
+            path: [0; libc::FILENAME_MAX as usize],
+            ret: 0
+        };
+        handler.cg_begin();
+        if handler.ret != C_GROUP_SUCCESS {
+            return Err(cg_get_error(handler.ret));
+        }
+
+        Ok(handler)
+    }
+
+    fn get_path(&self)->String{
+        unsafe {
+            std::ffi::CStr::from_ptr(self.path.as_ptr())
+                .to_string_lossy()
+                .into_owned()
+        }
+    }
+
+    pub fn cg_begin(&mut self) -> Option<String> {
+        unsafe {
+            let c_handler = &(self.handler) as *const *const libc::c_void;
+            self.ret = cgroup_get_subsys_mount_point_begin(
+                self.ctrl_name.as_ptr(),
+                c_handler,
+                self.path.as_mut_ptr()
+            );
+            info!("CGroupSubsysMountPointIterator::cg_begin[return code] = {}", self.ret);
+        }
+        if self.ret == C_GROUP_SUCCESS {
+            return Some(self.get_path());
+        }
+        None
+    }
+
+    pub fn cg_next(&mut self) -> Option<String> {
+        unsafe {
+            let c_handler = &(self.handler) as *const *const libc::c_void;
+            self.ret = cgroup_get_subsys_mount_point_next(c_handler,self.path.as_mut_ptr());
+            info!("CGroupSubsysMountPointIterator::cg_next[return code] = {}", self.ret);
+        }
+        if self.ret == C_GROUP_SUCCESS {
+            return Some(self.get_path());
+        }
+        None
+    }
+
+    pub fn cg_end(&mut self) {
+        unsafe {
+            let c_handler = &(self.handler) as *const *const libc::c_void;
+            let ret = cgroup_get_subsys_mount_point_end(c_handler);
+            info!("CGroupSubsysMountPointIterator::cg_end[return code] = {}", ret);
+        }
+    }
+}
+
+impl Iterator for CGroupSubsysMountPointIterator{
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.ret != 0 {
+            self.cg_end();
+            None
+        } else {
+            let path = self.get_path();
+            self.cg_next();
+            Some(path)
+        }
+    }
+}
+
+
+
+/// Process ids of a group read from cgroup.procs,
+/// in the order of the kernel which may repeat pids on cgroup v1
+pub struct CGroupProcsIterator {
+    lines: std::io::Lines<std::io::BufReader<std::fs::File>>,
+}
+
+impl CGroupProcsIterator {
+
+    /// cgroup.procs of the group in the hierarchy of the controller
+    pub fn from(cg_name:&str,ctrl_name:&str)->Result<Self,std::io::Error>{
+        let mount_point = CGroupInitializer::get_subsys_mount_point(ctrl_name)
+            .ok_or_else(|| cg_get_error(C_EC_GROUP_SUBSYS_NOT_MOUNTED))?;
+        let path = std::path::Path::new(&mount_point)
+            .join(cg_name.trim_start_matches('/'))
+            .join("cgroup.procs");
+        info!("CGroupProcsIterator::from[path] = {}",path.display());
+        Self::from_path(path)
+    }
+
+    pub fn from_path<P:AsRef<std::path::Path>>(path:P)->Result<Self,std::io::Error>{
+        use std::io::BufRead;
+        let file = std::fs::File::open(path)?;
+        Ok(Self{ lines: std::io::BufReader::new(file).lines() })
+    }
+}
+
+impl Iterator for CGroupProcsIterator{
+    type Item = i32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => {
+                    info!("CGroupProcsIterator::next[error msg] = {:?}",e);
+                    return None;
+                }
+            };
+            if let Ok(pid) = line.trim().parse::<i32>() {
+                return Some(pid);
+            }
+        }
+    }
+}
+
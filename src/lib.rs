@@ -5,3 +5,10 @@ pub mod manipulation;
 pub mod extend;
 pub mod iterators;
 pub mod config;
+pub mod cgconfig;
+pub mod templates;
+pub mod cgrules;
+pub mod tasks;
+pub mod rulesengd;
+pub mod tree;
+pub mod diff;
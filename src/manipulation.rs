@@ -7,6 +7,9 @@
 //!    - cgroup_new_cgroup
 //!    - cgroup_add_controller
 //!    - cgroup_get_controller
+//!    - cgroup_get_controller_count
+//!    - cgroup_get_controller_by_index
+//!    - cgroup_get_controller_name
 //!    - cgroup_free
 //!    - cgroup_free_controllers
 //!    - cgroup_create_cgroup
@@ -20,6 +23,7 @@
 //!    - cgroup_compare_controllers
 //!    - cgroup_set_uid_gid
 //!    - cgroup_get_uid_gid
+//!    - cgroup_set_permissions
 //!    - cgroup_add_value_*
 //!    - cgroup_get_value_*
 //!    - cgroup_set_value_*
@@ -58,8 +62,7 @@
 //! Usage(Create with params):
 //! ```
 //! use libcgroup_rs::initialization::CGroupInitializer;
-//! use libcgroup_rs::manipulation::CGroupBuilder;
-//! use libcgroup_rs::error::{cg_get_error, C_EC_GROUP_NOT_ALLOWED};
+//! use libcgroup_rs::manipulation::{CGroupBuilder, Permissions};
 //!
 //! fn main()->Result<(),Box<dyn std::error::Error>>{
 //!     CGroupInitializer::init()?;
@@ -77,6 +80,9 @@
 //!     // cfg = /cgroups/foo/cpu/cfs_period_us
 //!     ctrl.add_u64("cpu.cfs_period_us",100000)?;
 //!
+//!     // 0775 for the directory, 0664 for the control files and tasks
+//!     container.set_permissions(Permissions::new(0o775,0o664,0o664));
+//!
 //!     // create
 //!     container.create(0)?;
 //!
@@ -87,9 +93,104 @@
 
 use crate::prelude::*;
 use crate::error::*;
+use crate::config::Loader;
+use crate::tasks::{CGroupTask,UnchangeFlags};
+use crate::extend::{CGroupMount,CGroupVersion};
+use crate::iterators::{CGroupProcsIterator,CGroupWalkBuilder,CGroupWalkIterator,TaskKind,WalkOrder};
 use log::{info,error};
 
 
+/// Flags of cgroup_delete_cgroup_ext and cgroup_config_unload_config(CGFLAG_DELETE_*)
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+pub struct DeleteFlags(libc::c_int);
+
+impl DeleteFlags{
+    pub const NONE:DeleteFlags = DeleteFlags(0);
+    /// ignore errors caused by migration of tasks to parent group
+    pub const IGNORE_MIGRATION:DeleteFlags = DeleteFlags(1);
+    /// recursively delete all child groups
+    pub const RECURSIVE:DeleteFlags = DeleteFlags(2);
+    /// delete the group only if it is empty
+    pub const EMPTY_ONLY:DeleteFlags = DeleteFlags(4);
+
+    pub fn bits(&self)->libc::c_int{
+        self.0
+    }
+
+    pub fn from_bits(bits:libc::c_int)->Self{
+        DeleteFlags(bits)
+    }
+
+    pub fn contains(&self,other:DeleteFlags)->bool{
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for DeleteFlags{
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        DeleteFlags(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitOrAssign for DeleteFlags{
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+
+/// What `CGroupBuilder::delete_tree` does with the tasks of the groups
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum DeletePolicy{
+    /// tasks move to the parent of each deleted group, up to the parent of the tree
+    MoveToParent,
+    /// SIGKILL every task first
+    Kill,
+    /// nothing is deleted while a group has tasks
+    Fail,
+}
+
+
+/// Mode of libcgroup meaning "keep the system default"(NO_PERMS)
+pub const NO_PERMS:libc::mode_t = libc::mode_t::MAX;
+
+
+/// Mode bits of the group directory, control files and tasks file, `None` keeps the system default
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+pub struct Permissions{
+    pub dir_mode:Option<u32>,
+    pub file_mode:Option<u32>,
+    pub tasks_mode:Option<u32>,
+}
+
+impl Permissions{
+    pub fn new(dir_mode:u32,file_mode:u32,tasks_mode:u32)->Self{
+        Self{
+            dir_mode:Some(dir_mode),
+            file_mode:Some(file_mode),
+            tasks_mode:Some(tasks_mode),
+        }
+    }
+
+    /// Modes passed to cgroup_set_permissions as `(dir, file, tasks)`, NO_PERMS for unset modes
+    pub fn to_raw(&self)->(libc::mode_t,libc::mode_t,libc::mode_t){
+        let mode = |v:Option<u32>| v.map_or(NO_PERMS,|m| m as libc::mode_t);
+        (mode(self.dir_mode),mode(self.file_mode),mode(self.tasks_mode))
+    }
+
+    /// cgroup_set_permissions
+    pub(crate) fn apply(&self,cg:*mut cgroup){
+        let (dir_mode,file_mode,tasks_mode) = self.to_raw();
+        unsafe {
+            cgroup_set_permissions(cg,dir_mode,file_mode,tasks_mode);
+        }
+        info!("Permissions::apply = {:?}",self);
+    }
+}
+
+
 #[derive(Debug)]
 pub struct CGroupControllerBuilder{
     name: String,
@@ -100,8 +201,11 @@ pub struct CGroupControllerBuilder{
 
 #[derive(Debug)]
 pub struct CGroupBuilder<'a>{
-    name: &'a str,
+    name: std::borrow::Cow<'a,str>,
     c_groups: *mut cgroup,
+    owner_set: std::cell::Cell<bool>,
+    permissions: std::cell::Cell<Option<Permissions>>,
+    sticky: std::cell::Cell<Option<UnchangeFlags>>,
 }
 
 
@@ -109,13 +213,16 @@ pub struct CGroupBuilder<'a>{
 impl<'a> CGroupBuilder<'a>{
 
 
-    pub fn new(name:&'a str)->Result<Self,std::io::Error>{
+    pub fn new<N:Into<std::borrow::Cow<'a,str>>>(name:N)->Result<Self,std::io::Error>{
         let mut cg = Self{
-            name,
+            name:name.into(),
             c_groups:std::ptr::null_mut(),
+            owner_set:std::cell::Cell::new(false),
+            permissions:std::cell::Cell::new(None),
+            sticky:std::cell::Cell::new(None),
         };
         cg.c_groups = unsafe {
-            let c_name = std::ffi::CString::new(cg.name)?;
+            let c_name = std::ffi::CString::new(cg.name.as_ref())?;
             cgroup_new_cgroup(c_name.as_ptr())
         };
 
@@ -129,6 +236,14 @@ impl<'a> CGroupBuilder<'a>{
         self.c_groups.is_null()
     }
 
+    pub fn get_name(&self)->&str{
+        self.name.as_ref()
+    }
+
+    pub(crate) fn as_ptr(&self)->*mut cgroup{
+        self.c_groups
+    }
+
     pub fn add_controller(&self, ctrl_name: &str) ->Result<CGroupControllerBuilder, std::io::Error> {
         unsafe {
             let c_ctrl_name = std::ffi::CString::new(ctrl_name)?;
@@ -153,6 +268,28 @@ impl<'a> CGroupBuilder<'a>{
         }
     }
 
+    /// Names of the controllers added to the group(cgroup_get_controller_by_index)
+    pub fn get_controller_names(&self)->Vec<String>{
+        let mut names = Vec::new();
+        unsafe {
+            if self.c_groups.is_null() {
+                return names;
+            }
+            let count = cgroup_get_controller_count(self.c_groups);
+            for index in 0..count {
+                let c_ctrl_ptr = cgroup_get_controller_by_index(self.c_groups,index);
+                if c_ctrl_ptr.is_null() {
+                    continue;
+                }
+                let c_name = cgroup_get_controller_name(c_ctrl_ptr);
+                if !c_name.is_null() {
+                    names.push(std::ffi::CStr::from_ptr(c_name).to_string_lossy().into_owned());
+                }
+            }
+        }
+        names
+    }
+
     pub fn free(&self){
         unsafe {
             if !self.c_groups.is_null() {
@@ -173,7 +310,9 @@ impl<'a> CGroupBuilder<'a>{
         }
     }
 
+    /// Default permissions of `Loader::set_default` apply when `set_uid_pid`/`set_permissions` were not called
     pub fn create(&self,ignore_ownership:i32)->Result<(),std::io::Error>{
+        self.apply_permissions()?;
         unsafe {
             let c_ignore_ownership = libc::c_int::from(ignore_ownership);
             let ret = cgroup_create_cgroup(self.c_groups,c_ignore_ownership);
@@ -186,6 +325,7 @@ impl<'a> CGroupBuilder<'a>{
     }
 
     pub fn create_from_parent(&self,ignore_ownership:i32)->Result<(),std::io::Error>{
+        self.apply_permissions()?;
         unsafe {
             let c_ignore_ownership = libc::c_int::from(ignore_ownership);
             let ret = cgroup_create_cgroup_from_parent(self.c_groups,c_ignore_ownership);
@@ -209,6 +349,29 @@ impl<'a> CGroupBuilder<'a>{
         Ok(())
     }
 
+    /// Read the controllers and values of the existing group(cgroup_get_cgroup)
+    pub fn load(&self)->Result<(),std::io::Error>{
+        unsafe {
+            let ret = cgroup_get_cgroup(self.c_groups);
+            info!("CGroupBuilder::load[return code] = {}",ret);
+            if ret != C_GROUP_SUCCESS {
+                return Err(cg_get_error(ret));
+            }
+        }
+        Ok(())
+    }
+
+    /// Controller name to parameter values of the group in memory
+    pub fn get_values(&self)->Result<std::collections::BTreeMap<String,std::collections::BTreeMap<String,String>>,std::io::Error>{
+        let mut values = std::collections::BTreeMap::new();
+        for ctrl_name in self.get_controller_names() {
+            let ctrl = self.get_controller(&ctrl_name)?;
+            values.insert(ctrl_name,ctrl.get_values()?);
+        }
+        Ok(values)
+    }
+
+    /// Remove the group, its tasks move to the parent group(cgroup_delete_cgroup)
     pub fn delete(&self,ignore_migration:i32)->Result<(),std::io::Error>{
         unsafe {
             let c_ignore_migration = libc::c_int::from(ignore_migration);
@@ -221,10 +384,9 @@ impl<'a> CGroupBuilder<'a>{
         Ok(())
     }
 
-    pub fn delete_ext(&self,flags:i32)->Result<(),std::io::Error>{
+    pub fn delete_ext(&self,flags:DeleteFlags)->Result<(),std::io::Error>{
         unsafe {
-            let c_flags = libc::c_int::from(flags);
-            let ret = cgroup_delete_cgroup_ext(self.c_groups,c_flags);
+            let ret = cgroup_delete_cgroup_ext(self.c_groups,flags.bits());
             info!("CGroupBuilder::delete_ext[return code] = {}",ret);
             if ret != C_GROUP_SUCCESS {
                 return Err(cg_get_error(ret));
@@ -257,6 +419,39 @@ impl<'a> CGroupBuilder<'a>{
                 return Err(cg_get_error(ret));
             }
         }
+        self.owner_set.set(true);
+        Ok(())
+    }
+
+    /// Modes of the directory, control files and tasks file applied on `create`(cgroup_set_permissions)
+    pub fn set_permissions(&self,perms:Permissions){
+        perms.apply(self.c_groups);
+        self.permissions.set(Some(perms));
+    }
+
+    pub fn get_permissions(&self)->Option<Permissions>{
+        self.permissions.get()
+    }
+
+    /// Register attached pids as unchanged for cgrulesengd(cgroup_register_unchanged_process)
+    pub fn set_sticky(&self,flags:Option<UnchangeFlags>){
+        self.sticky.set(flags);
+    }
+
+    pub fn get_sticky(&self)->Option<UnchangeFlags>{
+        self.sticky.get()
+    }
+
+    fn apply_permissions(&self)->Result<(),std::io::Error>{
+        let default = Loader::get_default();
+        if let (false,Some(d)) = (self.owner_set.get(),default) {
+            d.apply_owner(self.c_groups)?;
+        }
+        match (self.permissions.get(),default) {
+            (Some(perms),_) => perms.apply(self.c_groups),
+            (None,Some(d)) => d.modes().apply(self.c_groups),
+            (None,None) => (),
+        }
         Ok(())
     }
 
@@ -291,49 +486,80 @@ impl<'a> CGroupBuilder<'a>{
 
 
 
+    /// Move the current task, registered as unchanged when sticky is set
     pub fn attach_task(&self)->Result<(),std::io::Error>{
         unsafe {
             let ret = cgroup_attach_task(self.c_groups);
             info!("CGroupBuilder::attach_task[return code] = {}",ret);
+            if ret != C_GROUP_SUCCESS {
+                return Err(cg_get_error(ret));
+            }
+        }
+        if let Some(flags) = self.sticky.get() {
+            CGroupTask::register_unchanged(unsafe { libc::getpid() },flags)?;
         }
-
         Ok(())
     }
 
+    /// Move the task `pid`, registered as unchanged when sticky is set
     pub fn attach_task_pid(&self,pid:i32)->Result<(),std::io::Error>{
         unsafe {
             let c_pid = libc::pid_t::from(pid);
             let ret = cgroup_attach_task_pid(self.c_groups,c_pid);
             info!("CGroupBuilder::attach_task_pid[return code] = {}",ret);
+            if ret != C_GROUP_SUCCESS {
+                return Err(cg_get_error(ret));
+            }
+        }
+        if let Some(flags) = self.sticky.get() {
+            CGroupTask::register_unchanged(pid,flags)?;
         }
         Ok(())
     }
 
+    /// Run /bin/sh in the group and wait for it. The parent attaches the child while it
+    /// waits on a pipe, so the child only makes async-signal-safe calls before exec
     pub fn attach_task_shell(&self)->Result<(),std::io::Error>{
 
+        let c_exec_app = std::ffi::CString::new("/bin/sh")?;
+        let exec_failed = b"attach_task_shell: exec /bin/sh failed\n";
         unsafe {
+            let mut fds = [0 as libc::c_int;2];
+            if libc::pipe2(fds.as_mut_ptr(),libc::O_CLOEXEC) != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            let (read_fd,write_fd) = (fds[0],fds[1]);
+
             let pid = libc::fork();
             if pid < 0 {
+                libc::close(read_fd);
+                libc::close(write_fd);
                 return Err(std::io::Error::new(std::io::ErrorKind::Other,"Failed by fork"))
             }else if pid == 0 {
-                let pid = libc::getpid();
-                self.attach_task_pid(pid)?;
-
-                let c_exec_app = std::ffi::CString::new("/bin/sh")?;
-                let ret = libc::execl(c_exec_app.as_ptr(),std::ptr::null());
-                if ret != C_GROUP_SUCCESS {
-                    return Err(std::io::Error::new(
-                       std::io::Error::from_raw_os_error(ret).kind(),
-                        "Failed by Attach CGroup Shell"
-                    ));
+                // the child never returns into the caller
+                libc::close(write_fd);
+                let mut attached = 0u8;
+                let n = libc::read(read_fd,&mut attached as *mut u8 as *mut libc::c_void,1);
+                libc::close(read_fd);
+                if n != 1 || attached != 1 {
+                    libc::_exit(1);
                 }
 
+                libc::execl(c_exec_app.as_ptr(),std::ptr::null::<libc::c_char>());
+                libc::write(2,exec_failed.as_ptr() as *const libc::c_void,exec_failed.len());
+                libc::_exit(127);
             }else {
-                let pid = libc::getpid();
+                libc::close(read_fd);
+                let attach = self.attach_task_pid(pid);
+                let attached = u8::from(attach.is_ok());
+                libc::write(write_fd,&attached as *const u8 as *const libc::c_void,1);
+                libc::close(write_fd);
+
                 let mut status = libc::c_int::from(0 as i32);
-                let ppid = libc::wait(&mut status);
-                info!("Fork Parent = {}",pid);
-                info!("Fork Child = {}",ppid);
+                let ret = libc::waitpid(pid,&mut status,0);
+                info!("Fork Parent = {}",libc::getpid());
+                info!("Fork Child = {}",ret);
+                attach?;
                 if status != C_GROUP_SUCCESS {
                     return Err(std::io::Error::from_raw_os_error(status));
                 }
@@ -341,6 +567,213 @@ impl<'a> CGroupBuilder<'a>{
         }
         Ok(())
     }
+
+    /// Signal every process of the group and its children, waiting at most 5 seconds,
+    /// returns the pids still alive(empty when the group was emptied)
+    pub fn kill(&self,signal:i32)->Result<Vec<i32>,std::io::Error>{
+        self.kill_timeout(signal,std::time::Duration::from_secs(5))
+    }
+
+    /// The signal is sent once, then the group is polled until it is empty or the timeout elapses.
+    /// SIGKILL uses cgroup.kill when the kernel has it(5.14+), otherwise the group is frozen,
+    /// signalled and thawed again. The calling process is never signalled nor reported
+    pub fn kill_timeout(&self,signal:i32,timeout:std::time::Duration)->Result<Vec<i32>,std::io::Error>{
+        self.refuse_root()?;
+        let dirs = self.group_dirs()?;
+        if dirs.is_empty() {
+            return Err(cg_get_error(C_EC_GROUP_NOT_EXIST));
+        }
+
+        let self_pid = unsafe { libc::getpid() } as i32;
+        let pids = Self::group_pids(&dirs);
+        let kill_file = dirs.iter()
+            .filter(|(mount,_)| mount.version == CGroupVersion::V2)
+            .map(|(_,dir)| dir.join("cgroup.kill"))
+            .find(|path| path.exists());
+
+        match (&kill_file,signal == libc::SIGKILL) {
+            (Some(path),true) if !pids.contains(&self_pid) => std::fs::write(path,"1")?,
+            _ => {
+                Self::freeze(&dirs,true);
+                let pids:Vec<i32> = Self::group_pids(&dirs).into_iter().filter(|pid| *pid != self_pid).collect();
+                let result = Self::signal_pids(&pids,signal);
+                Self::freeze(&dirs,false);
+                result?;
+            }
+        }
+
+        let start = std::time::Instant::now();
+        loop {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            let pids:Vec<i32> = Self::group_pids(&dirs).into_iter().filter(|pid| *pid != self_pid).collect();
+            info!("CGroupBuilder::kill[remaining] = {}",pids.len());
+            if pids.is_empty() {
+                return Ok(pids);
+            }
+            if start.elapsed() >= timeout {
+                error!("CGroupBuilder::kill[not killed] = {:?}",pids);
+                return Ok(pids);
+            }
+        }
+    }
+
+    /// Delete the group and every child group deepest first in the hierarchies of its controllers,
+    /// tasks are handled by the policy before anything is removed, the root group is refused
+    pub fn delete_tree(&self,policy:DeletePolicy)->Result<(),std::io::Error>{
+        self.refuse_root()?;
+        let controllers = self.get_controller_names();
+        if controllers.is_empty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("No controller added to group = {}",self.name)
+            ));
+        }
+
+        let mut groups:Vec<String> = Vec::new();
+        for ctrl_name in controllers.iter() {
+            let walk = CGroupWalkBuilder::new(ctrl_name,&self.name)
+                .set_order(WalkOrder::Post)
+                .build()?;
+            for group in walk.groups()? {
+                if !groups.contains(&group) {
+                    groups.push(group);
+                }
+            }
+        }
+        // children before parents across hierarchies too
+        groups.sort_by_key(|group| std::cmp::Reverse(group.matches('/').count()));
+
+        let flags = match policy {
+            DeletePolicy::MoveToParent => DeleteFlags::NONE,
+            DeletePolicy::Kill => {
+                let remaining = self.kill(libc::SIGKILL)?;
+                if !remaining.is_empty() {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::TimedOut,
+                        format!("Failed to kill = {:?}",remaining)
+                    ));
+                }
+                DeleteFlags::EMPTY_ONLY
+            }
+            DeletePolicy::Fail => {
+                for ctrl_name in controllers.iter() {
+                    let tasks = CGroupWalkIterator::from(ctrl_name,&self.name,0)?.tasks(TaskKind::Procs)?;
+                    if let Some((group,pid)) = tasks.first() {
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::Other,
+                            format!("Group {} is busy with pid = {}",group,pid)
+                        ));
+                    }
+                }
+                DeleteFlags::EMPTY_ONLY
+            }
+        };
+
+        for group in groups.iter() {
+            info!("CGroupBuilder::delete_tree[group] = {}",group);
+            let cg = CGroupBuilder::new(group.as_str())?;
+            for ctrl_name in controllers.iter() {
+                cg.add_controller(ctrl_name)?;
+            }
+            cg.delete_ext(flags)?;
+        }
+        Ok(())
+    }
+
+    fn refuse_root(&self)->Result<(),std::io::Error>{
+        if self.name.trim_matches('/').is_empty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Refusing to operate on the root group"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Directory of the group in the mounted hierarchies of its controllers,
+    /// cgroup2 holds the controllers not bound to a v1 hierarchy
+    fn group_dirs(&self)->Result<Vec<(CGroupMount,std::path::PathBuf)>,std::io::Error>{
+        let controllers = self.get_controller_names();
+        if controllers.is_empty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("No controller added to group = {}",self.name)
+            ));
+        }
+
+        let mounts = CGroupMount::from_self()?;
+        let on_v1 = |mount:&CGroupMount,ctrl_name:&str| {
+            mount.version == CGroupVersion::V1 && match ctrl_name.strip_prefix("name=") {
+                Some(name) => mount.name.as_deref() == Some(name),
+                None => mount.has_controller(ctrl_name),
+            }
+        };
+        let name = self.name.trim_start_matches('/');
+        Ok(mounts.iter()
+            .filter(|mount| controllers.iter().any(|ctrl_name| match mount.version {
+                CGroupVersion::V1 => on_v1(mount,ctrl_name),
+                CGroupVersion::V2 => !mounts.iter().any(|m| on_v1(m,ctrl_name)),
+            }))
+            .filter_map(|mount| {
+                let dir = std::path::Path::new(&mount.mount_point).join(name);
+                if dir.is_dir() { Some((mount.clone(),dir)) } else { None }
+            })
+            .collect())
+    }
+
+    /// cgroup.freeze on cgroup2, freezer.state of the v1 freezer, errors are ignored
+    fn freeze(dirs:&[(CGroupMount,std::path::PathBuf)],frozen:bool){
+        for (mount,dir) in dirs.iter() {
+            let (file,value) = match mount.version {
+                CGroupVersion::V2 => ("cgroup.freeze",if frozen { "1" } else { "0" }),
+                CGroupVersion::V1 if mount.has_controller("freezer") => {
+                    ("freezer.state",if frozen { "FROZEN" } else { "THAWED" })
+                }
+                CGroupVersion::V1 => continue,
+            };
+            if let Err(e) = std::fs::write(dir.join(file),value) {
+                info!("CGroupBuilder::freeze[error msg] = {:?}",e);
+            }
+        }
+    }
+
+    /// Pids of the group and its children of every hierarchy, sorted
+    fn group_pids(dirs:&[(CGroupMount,std::path::PathBuf)])->Vec<i32>{
+        fn walk(dir:&std::path::Path,pids:&mut Vec<i32>){
+            if let Ok(procs) = CGroupProcsIterator::from_path(dir.join("cgroup.procs")) {
+                pids.extend(procs);
+            }
+            if let Ok(entries) = std::fs::read_dir(dir) {
+                for entry in entries.flatten() {
+                    if entry.file_type().map_or(false,|t| t.is_dir()) {
+                        walk(&entry.path(),pids);
+                    }
+                }
+            }
+        }
+
+        let mut pids = Vec::new();
+        for (_,dir) in dirs.iter() {
+            walk(dir,&mut pids);
+        }
+        pids.sort_unstable();
+        pids.dedup();
+        pids
+    }
+
+    fn signal_pids(pids:&[i32],signal:i32)->Result<(),std::io::Error>{
+        for pid in pids.iter() {
+            let ret = unsafe { libc::kill(*pid as libc::pid_t,signal) };
+            if ret < 0 {
+                let e = std::io::Error::last_os_error();
+                // exited in the meantime
+                if e.raw_os_error() != Some(libc::ESRCH) {
+                    return Err(e);
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 impl CGroupControllerBuilder {
@@ -352,6 +785,38 @@ impl CGroupControllerBuilder {
         self.c_groups_ctrl.is_null()
     }
 
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Names of the parameters of the controller(cgroup_get_value_name)
+    pub fn get_value_names(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        unsafe {
+            if self.c_groups_ctrl.is_null() {
+                return names;
+            }
+            let count = cgroup_get_value_name_count(self.c_groups_ctrl);
+            for idx in 0..count {
+                let c_name = cgroup_get_value_name(self.c_groups_ctrl, idx);
+                if !c_name.is_null() {
+                    names.push(std::ffi::CStr::from_ptr(c_name).to_string_lossy().into_owned());
+                }
+            }
+        }
+        names
+    }
+
+    /// Every parameter of the controller with its value as string
+    pub fn get_values(&self) -> Result<std::collections::BTreeMap<String, String>, std::io::Error> {
+        let mut values = std::collections::BTreeMap::new();
+        for name in self.get_value_names() {
+            let value = self.get_str(&name)?;
+            values.insert(name, value);
+        }
+        Ok(values)
+    }
+
     pub fn add_str(&self, name: &str, value: &str) -> Result<(), std::io::Error> {
         unsafe {
             let c_name = std::ffi::CString::new(name)?;
@@ -574,11 +1039,20 @@ impl<'a> Clone for CGroupBuilder<'a>{
                     if ret == C_GROUP_SUCCESS {
                         clone.c_groups = c_sdt;
                     }
+                    clone.owner_set.set(self.owner_set.get());
+                    clone.permissions.set(self.permissions.get());
+                    clone.sticky.set(self.sticky.get());
                     clone
                 }
                 Err(e) => {
                     error!("CGroupBuilder::clone[error msg] = {:?}",e);
-                    Self{name:self.name,c_groups:std::ptr::null_mut()}
+                    Self{
+                        name:self.name.clone(),
+                        c_groups:std::ptr::null_mut(),
+                        owner_set:self.owner_set.clone(),
+                        permissions:self.permissions.clone(),
+                        sticky:self.sticky.clone()
+                    }
                 }
             }
         }
@@ -593,6 +1067,9 @@ impl<'a> Clone for CGroupBuilder<'a>{
                 self.c_groups = c_sdt;
             }
         }
+        self.owner_set.set(source.owner_set.get());
+        self.permissions.set(source.permissions.get());
+        self.sticky.set(source.sticky.get());
     }
 }
 
@@ -202,7 +202,7 @@ extern "C" {
 
     // 1.Initialize
     pub fn cgroup_init()->libc::c_int;
-    pub fn cgroup_get_subsys_mount_point(ctrl:*const libc::c_char,mount_point:*const *const libc::c_char)->libc::c_int;
+    pub fn cgroup_get_subsys_mount_point(ctrl:*const libc::c_char,mount_point:*mut *mut libc::c_char)->libc::c_int;
 
 
 
@@ -212,6 +212,9 @@ extern "C" {
 
     pub fn cgroup_add_controller(cg:*mut cgroup,name:*const libc::c_char)->*mut cgroup_controller;
     pub fn cgroup_get_controller(cg:*mut cgroup,name:*const libc::c_char)->*mut cgroup_controller;
+    pub fn cgroup_get_controller_count(cg:*mut cgroup)->libc::c_int;
+    pub fn cgroup_get_controller_by_index(cg:*mut cgroup,index:libc::c_int)->*mut cgroup_controller;
+    pub fn cgroup_get_controller_name(controller:*mut cgroup_controller)->*mut libc::c_char;
     pub fn cgroup_free(cg:*const *const cgroup);
     pub fn cgroup_free_controllers(cg:*mut cgroup);
 
@@ -238,6 +241,12 @@ extern "C" {
         ctrl_uid: *const libc::uid_t,
         ctrl_gid: *const libc::gid_t
     )->libc::c_int;
+    pub fn cgroup_set_permissions(
+        cg:*mut cgroup,
+        control_dperm: libc::mode_t,
+        control_fperm: libc::mode_t,
+        task_fperm: libc::mode_t
+    );
 
 
 
@@ -328,9 +337,10 @@ extern "C" {
         base_level: *mut libc::c_int
     )->libc::c_int;
     pub fn cgroup_walk_tree_next(
+        depth: libc::c_int,
         handle: *const *const libc::c_void,
         info: *mut CGroupFileInfo,
-        base_level: *mut libc::c_int
+        base_level: libc::c_int
     )->libc::c_int;
     pub fn cgroup_walk_tree_end(handle: *const *const libc::c_void)->libc::c_int;
     pub fn cgroup_walk_tree_set_flags(handle: *const *const libc::c_void,flags:libc::c_int)->libc::c_int;
@@ -386,6 +396,17 @@ extern "C" {
     pub fn cgroup_get_all_controller_end(handle: *const *const libc::c_void) -> libc::c_int;
 
 
+
+    pub fn cgroup_get_subsys_mount_point_begin(ctrl_name:*const libc::c_char,
+                                               handle: *const *const libc::c_void,
+                                               path: *mut libc::c_char)
+                                               -> libc::c_int;
+    pub fn cgroup_get_subsys_mount_point_next(handle: *const *const libc::c_void,
+                                              path: *mut libc::c_char)
+                                              -> libc::c_int;
+    pub fn cgroup_get_subsys_mount_point_end(handle: *const *const libc::c_void) -> libc::c_int;
+
+
     // 4. Manipulation with Tasks
     pub fn cgroup_attach_task(cg:*mut cgroup)->libc::c_int;
     pub fn cgroup_attach_task_pid(cg:*mut cgroup,pid:libc::pid_t)->libc::c_int;
@@ -394,11 +415,48 @@ extern "C" {
         ctrl:*const libc::c_char,
         current_path:*mut *mut libc::c_char
     )->libc::c_int;
+    pub fn cgroup_change_cgroup_path(
+        path:*const libc::c_char,
+        pid:libc::pid_t,
+        controllers:*const *const libc::c_char
+    )->libc::c_int;
+    pub fn cgroup_change_all_cgroups()->libc::c_int;
+    pub fn cgroup_register_unchanged_process(pid:libc::pid_t,flags:libc::c_int)->libc::c_int;
+    pub fn cgroup_get_procs(
+        name:*const libc::c_char,
+        controller:*const libc::c_char,
+        pids:*mut *mut libc::pid_t,
+        size:*mut libc::c_int
+    )->libc::c_int;
+    pub fn cgroup_init_rules_cache()->libc::c_int;
+    pub fn cgroup_reload_cached_rules()->libc::c_int;
+    pub fn cgroup_change_cgroup_flags(
+        uid:libc::uid_t,
+        gid:libc::gid_t,
+        procname:*const libc::c_char,
+        pid:libc::pid_t,
+        flags:libc::c_int
+    )->libc::c_int;
+    pub fn cgroup_change_cgroup_uid_gid_flags(
+        uid:libc::uid_t,
+        gid:libc::gid_t,
+        pid:libc::pid_t,
+        flags:libc::c_int
+    )->libc::c_int;
 
 
     // 5. Configuration
     pub fn cgroup_config_load_config(pathname:*const libc::c_char)->libc::c_int;
     pub fn cgroup_unload_cgroups()->libc::c_int;
+    pub fn cgroup_config_unload_config(pathname:*const libc::c_char,flags:libc::c_int)->libc::c_int;
+    pub fn cgroup_config_set_default(new_default:*mut cgroup)->libc::c_int;
+    pub fn cgroup_init_templates_cache(pathname:*const libc::c_char)->libc::c_int;
+    pub fn cgroup_reload_cached_templates(pathname:*const libc::c_char)->libc::c_int;
+    pub fn cgroup_config_create_template_group(
+        cg:*mut cgroup,
+        template_name:*const libc::c_char,
+        flags:libc::c_int
+    )->libc::c_int;
 
 
     // 6.Error handling
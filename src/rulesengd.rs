@@ -0,0 +1,562 @@
+//!
+//! ### Rules engine daemon(cgrulesengd)
+//!
+//! URL: [cgrulesengd](https://man7.org/linux/man-pages/man8/cgrulesengd.8.html)
+//!
+//! Listens to fork/exec/uid/gid events of the process connector(netlink) and moves
+//! every exec'd or re-identified process to the destination of its first matching rule.
+//! Processes registered through `cgroup_register_unchanged_process` are left alone.
+//!
+//! Functions:
+//!    - cgroup_change_cgroup_path
+//!
+//! Usage:
+//! ```no_run
+//! use libcgroup_rs::initialization::CGroupInitializer;
+//! use libcgroup_rs::rulesengd::{FileRuleSource, ProcConnector, RulesEngine};
+//!
+//! fn main()->Result<(),Box<dyn std::error::Error>>{
+//!     CGroupInitializer::init()?;
+//!
+//!     // cgrules.conf and cgrules.d/*.conf, reloaded when they change
+//!     let mut engine = RulesEngine::new(FileRuleSource::system())?;
+//!     engine.listen_unchanged(libcgroup_rs::rulesengd::CGRULE_CGRED_SOCKET_PATH)?;
+//!
+//!     let connector = ProcConnector::new()?;
+//!     engine.run(&connector)?;
+//!     Ok(())
+//! }
+//! ```
+//!
+//! Usage(Rules as data):
+//! ```
+//! use libcgroup_rs::cgrules::{CGRules, ProcessInfo};
+//! use libcgroup_rs::rulesengd::RulesEngine;
+//!
+//! fn main()->Result<(),Box<dyn std::error::Error>>{
+//!     let rules = CGRules::parse("*    cpu,memory    users/%u/")?;
+//!     let engine = RulesEngine::new(rules)?;
+//!
+//!     let process = ProcessInfo::for_pid(std::process::id() as i32)?;
+//!     for (destination,controllers) in engine.plan(&process) {
+//!         println!("{} = {:?}",destination,controllers);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+//!
+
+
+use crate::cgrules::*;
+use crate::tasks::{CGroupTask,UnchangeFlags};
+use log::{info,warn,error,debug};
+use std::os::unix::io::{AsRawFd,RawFd};
+
+
+/// Socket of cgroup_register_unchanged_process
+pub const CGRULE_CGRED_SOCKET_PATH:&str = "/var/run/cgred.socket";
+/// Answer expected by cgroup_register_unchanged_process
+pub const CGRULE_SUCCESS_STORE_PID:&str = "SUCCESS_STORE_PID";
+
+const CN_IDX_PROC:u32 = 0x1;
+const CN_VAL_PROC:u32 = 0x1;
+const PROC_CN_MCAST_LISTEN:u32 = 1;
+const PROC_CN_MCAST_IGNORE:u32 = 2;
+
+const PROC_EVENT_FORK:u32 = 0x00000001;
+const PROC_EVENT_EXEC:u32 = 0x00000002;
+const PROC_EVENT_UID:u32 = 0x00000004;
+const PROC_EVENT_GID:u32 = 0x00000040;
+const PROC_EVENT_EXIT:u32 = 0x80000000;
+
+const NLMSG_HDRLEN:usize = 16;
+const CN_MSG_LEN:usize = 20;
+const PROC_EVENT_HDRLEN:usize = 16;
+
+
+/// Event of the process connector(struct proc_event)
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum ProcEvent{
+    Fork{parent_pid:i32,parent_tgid:i32,child_pid:i32,child_tgid:i32},
+    Exec{pid:i32,tgid:i32},
+    Uid{pid:i32,tgid:i32,ruid:u32,euid:u32},
+    Gid{pid:i32,tgid:i32,rgid:u32,egid:u32},
+    Exit{pid:i32,tgid:i32,exit_code:u32},
+    /// ack of the listen request and the events not handled here
+    Other(u32),
+}
+
+impl ProcEvent{
+
+    /// Decode a struct proc_event in host byte order
+    pub fn parse(buf:&[u8])->Option<Self>{
+        let what = read_u32(buf,0)?;
+        let data = buf.get(PROC_EVENT_HDRLEN..)?;
+        let field = |idx:usize| read_u32(data,idx * 4);
+        let event = match what {
+            PROC_EVENT_FORK => ProcEvent::Fork{
+                parent_pid:field(0)? as i32,
+                parent_tgid:field(1)? as i32,
+                child_pid:field(2)? as i32,
+                child_tgid:field(3)? as i32,
+            },
+            PROC_EVENT_EXEC => ProcEvent::Exec{
+                pid:field(0)? as i32,
+                tgid:field(1)? as i32,
+            },
+            PROC_EVENT_UID => ProcEvent::Uid{
+                pid:field(0)? as i32,
+                tgid:field(1)? as i32,
+                ruid:field(2)?,
+                euid:field(3)?,
+            },
+            PROC_EVENT_GID => ProcEvent::Gid{
+                pid:field(0)? as i32,
+                tgid:field(1)? as i32,
+                rgid:field(2)?,
+                egid:field(3)?,
+            },
+            PROC_EVENT_EXIT => ProcEvent::Exit{
+                pid:field(0)? as i32,
+                tgid:field(1)? as i32,
+                exit_code:field(2)?,
+            },
+            what => ProcEvent::Other(what),
+        };
+        Some(event)
+    }
+
+    pub fn pid(&self)->Option<i32>{
+        match *self {
+            ProcEvent::Fork{child_pid,..} => Some(child_pid),
+            ProcEvent::Exec{pid,..} |
+            ProcEvent::Uid{pid,..} |
+            ProcEvent::Gid{pid,..} |
+            ProcEvent::Exit{pid,..} => Some(pid),
+            ProcEvent::Other(_) => None,
+        }
+    }
+}
+
+fn read_u32(buf:&[u8],offset:usize)->Option<u32>{
+    let bytes = buf.get(offset..offset + 4)?;
+    Some(u32::from_ne_bytes([bytes[0],bytes[1],bytes[2],bytes[3]]))
+}
+
+
+/// Netlink socket subscribed to the process connector, needs CAP_NET_ADMIN
+pub struct ProcConnector{
+    fd:RawFd,
+}
+
+impl ProcConnector{
+
+    pub fn new()->Result<Self,std::io::Error>{
+        unsafe {
+            let fd = libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_DGRAM | libc::SOCK_CLOEXEC,
+                libc::NETLINK_CONNECTOR
+            );
+            if fd < 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            let connector = Self{ fd };
+
+            let mut addr:libc::sockaddr_nl = std::mem::zeroed();
+            addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+            // port assigned by the kernel, getpid() is taken by any other netlink socket of the process
+            addr.nl_pid = 0;
+            addr.nl_groups = CN_IDX_PROC;
+            let ret = libc::bind(
+                fd,
+                &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+                std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t
+            );
+            info!("ProcConnector::new[bind] = {}",ret);
+            if ret < 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+
+            connector.send_op(PROC_CN_MCAST_LISTEN)?;
+            Ok(connector)
+        }
+    }
+
+    /// nlmsghdr + cn_msg + op of the multicast request
+    fn send_op(&self,op:u32)->Result<(),std::io::Error>{
+        let len = NLMSG_HDRLEN + CN_MSG_LEN + 4;
+        let mut buf:Vec<u8> = Vec::with_capacity(len);
+        // nlmsghdr
+        buf.extend_from_slice(&(len as u32).to_ne_bytes());
+        buf.extend_from_slice(&(libc::NLMSG_DONE as u16).to_ne_bytes());
+        buf.extend_from_slice(&0u16.to_ne_bytes());
+        buf.extend_from_slice(&0u32.to_ne_bytes());
+        buf.extend_from_slice(&(unsafe { libc::getpid() } as u32).to_ne_bytes());
+        // cn_msg
+        buf.extend_from_slice(&CN_IDX_PROC.to_ne_bytes());
+        buf.extend_from_slice(&CN_VAL_PROC.to_ne_bytes());
+        buf.extend_from_slice(&0u32.to_ne_bytes());
+        buf.extend_from_slice(&0u32.to_ne_bytes());
+        buf.extend_from_slice(&4u16.to_ne_bytes());
+        buf.extend_from_slice(&0u16.to_ne_bytes());
+        // enum proc_cn_mcast_op
+        buf.extend_from_slice(&op.to_ne_bytes());
+
+        let ret = unsafe {
+            libc::send(self.fd,buf.as_ptr() as *const libc::c_void,buf.len(),0)
+        };
+        if ret < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Block until the next datagram and decode its events
+    pub fn recv(&self)->Result<Vec<ProcEvent>,std::io::Error>{
+        let mut buf = [0u8;4096];
+        let len = unsafe {
+            libc::recv(self.fd,buf.as_mut_ptr() as *mut libc::c_void,buf.len(),0)
+        };
+        if len < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(Self::parse_messages(&buf[..len as usize]))
+    }
+
+    /// Events of every netlink message of a datagram
+    pub fn parse_messages(mut buf:&[u8])->Vec<ProcEvent>{
+        let mut events = Vec::new();
+        while let Some(msg_len) = read_u32(buf,0) {
+            let msg_len = msg_len as usize;
+            if msg_len < NLMSG_HDRLEN || msg_len > buf.len() {
+                break;
+            }
+            if let Some(event) = buf
+                .get(NLMSG_HDRLEN + CN_MSG_LEN..msg_len)
+                .and_then(ProcEvent::parse) {
+                events.push(event);
+            }
+            // NLMSG_ALIGN
+            let next = (msg_len + 3) & !3;
+            buf = buf.get(next..).unwrap_or(&[]);
+        }
+        events
+    }
+}
+
+impl AsRawFd for ProcConnector{
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl Drop for ProcConnector{
+    fn drop(&mut self) {
+        if let Err(e) = self.send_op(PROC_CN_MCAST_IGNORE) {
+            warn!("ProcConnector::drop[error msg] = {:?}",e);
+        }
+        unsafe {
+            libc::close(self.fd);
+        }
+    }
+}
+
+
+/// Where the engine takes its rules from
+pub trait RuleSource{
+
+    fn load(&mut self)->Result<CGRules,std::io::Error>;
+
+    /// Polled between events, the rules are loaded again when true
+    fn changed(&mut self)->bool{
+        false
+    }
+}
+
+impl RuleSource for CGRules{
+    fn load(&mut self)->Result<CGRules,std::io::Error>{
+        Ok(self.clone())
+    }
+}
+
+
+/// Rules files watched by modification time
+#[derive(Debug,Clone,Default)]
+pub struct FileRuleSource{
+    /// None for the files read by libcgroup
+    path:Option<std::path::PathBuf>,
+    modified:Option<std::time::SystemTime>,
+}
+
+impl FileRuleSource{
+
+    pub fn new<P:Into<std::path::PathBuf>>(path:P)->Self{
+        Self{
+            path:Some(path.into()),
+            modified:None,
+        }
+    }
+
+    /// cgrules.conf then cgrules.d/*.conf
+    pub fn system()->Self{
+        Self::default()
+    }
+
+    fn last_modified(&self)->Option<std::time::SystemTime>{
+        let modified = |path:&std::path::Path| {
+            std::fs::metadata(path).and_then(|m| m.modified()).ok()
+        };
+        match &self.path {
+            Some(path) => modified(path),
+            None => {
+                let dir = std::path::Path::new(CGRULES_CONF_DIR);
+                let files = std::fs::read_dir(dir)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|entry| entry.ok().map(|e| e.path()));
+                std::iter::once(std::path::PathBuf::from(CGRULES_CONF_FILE))
+                    .chain(std::iter::once(dir.to_path_buf()))
+                    .chain(files)
+                    .filter_map(|path| modified(&path))
+                    .max()
+            }
+        }
+    }
+}
+
+impl RuleSource for FileRuleSource{
+
+    fn load(&mut self)->Result<CGRules,std::io::Error>{
+        self.modified = self.last_modified();
+        match &self.path {
+            Some(path) => CGRules::from_file(&path.to_string_lossy()),
+            None => RulesCache::rules_config(),
+        }
+    }
+
+    fn changed(&mut self)->bool{
+        self.last_modified() != self.modified
+    }
+}
+
+
+/// Classification loop of cgrulesengd
+pub struct RulesEngine<S:RuleSource>{
+    source:S,
+    rules:CGRules,
+    unchanged:std::collections::HashMap<i32,UnchangeFlags>,
+    listener:Option<std::os::unix::net::UnixListener>,
+}
+
+impl<S:RuleSource> RulesEngine<S>{
+
+    pub fn new(mut source:S)->Result<Self,std::io::Error>{
+        let rules = source.load()?;
+        info!("RulesEngine::new[rules] = {}",rules.rules.len());
+        Ok(Self{
+            source,
+            rules,
+            unchanged:std::collections::HashMap::new(),
+            listener:None,
+        })
+    }
+
+    pub fn get_rules(&self)->&CGRules{
+        &self.rules
+    }
+
+    pub fn reload(&mut self)->Result<(),std::io::Error>{
+        self.rules = self.source.load()?;
+        info!("RulesEngine::reload[rules] = {}",self.rules.rules.len());
+        Ok(())
+    }
+
+    /// Same as a request of cgroup_register_unchanged_process,
+    /// `UnchangeFlags::CANCEL` classifies the pid again on its next event
+    pub fn add_unchanged(&mut self,pid:i32,flags:UnchangeFlags){
+        if flags.contains(UnchangeFlags::CANCEL) {
+            self.unchanged.remove(&pid);
+        } else {
+            self.unchanged.insert(pid,flags);
+        }
+    }
+
+    pub fn is_unchanged(&self,pid:i32)->bool{
+        self.unchanged.contains_key(&pid)
+    }
+
+    /// Accept cgroup_register_unchanged_process requests on a unix socket
+    pub fn listen_unchanged(&mut self,pathname:&str)->Result<(),std::io::Error>{
+        match std::fs::remove_file(pathname) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+            _ => (),
+        }
+        let listener = std::os::unix::net::UnixListener::bind(pathname)?;
+        listener.set_nonblocking(true)?;
+        self.listener = Some(listener);
+        Ok(())
+    }
+
+    /// Destinations of the first matching rule grouped by path,
+    /// empty controllers stand for every controller
+    pub fn plan(&self,process:&ProcessInfo)->Vec<(String,Vec<String>)>{
+        let m = match self.rules.classify(process) {
+            Some(m) if !m.ignore => m,
+            _ => return Vec::new(),
+        };
+        // destination, every controller("*"), controllers
+        let mut plan:Vec<(String,bool,Vec<String>)> = Vec::new();
+        for (ctrl,destination) in m.destinations.into_iter() {
+            let idx = match plan.iter().position(|(d,_,_)| *d == destination) {
+                Some(idx) => idx,
+                None => {
+                    plan.push((destination,false,Vec::new()));
+                    plan.len() - 1
+                }
+            };
+            if ctrl == "*" {
+                plan[idx].1 = true;
+            } else {
+                plan[idx].2.push(ctrl);
+            }
+        }
+        plan.into_iter()
+            .map(|(destination,all,controllers)| (destination,if all { Vec::new() } else { controllers }))
+            .collect()
+    }
+
+    /// Move the pid according to the rules(cgroup_change_cgroup_path)
+    pub fn classify(&self,pid:i32)->Result<(),std::io::Error>{
+        if self.is_unchanged(pid) {
+            debug!("RulesEngine::classify[unchanged] = {}",pid);
+            return Ok(());
+        }
+        let process = ProcessInfo::for_pid(pid)?;
+        for (destination,controllers) in self.plan(&process) {
+            let controllers:Vec<&str> = controllers.iter().map(|c| c.as_str()).collect();
+            CGroupTask::change_path(&destination,pid,&controllers)?;
+            info!("RulesEngine::classify[{}] = {} {:?}",pid,destination,controllers);
+        }
+        Ok(())
+    }
+
+    pub fn handle(&mut self,event:&ProcEvent)->Result<(),std::io::Error>{
+        match *event {
+            ProcEvent::Fork{parent_pid,child_pid,..} => {
+                if let Some(flags) = self.unchanged.get(&parent_pid).copied() {
+                    if flags.contains(UnchangeFlags::CHILDREN) {
+                        self.unchanged.insert(child_pid,flags);
+                    }
+                }
+                Ok(())
+            }
+            ProcEvent::Exec{pid,..} |
+            ProcEvent::Uid{pid,..} |
+            ProcEvent::Gid{pid,..} => self.classify(pid),
+            ProcEvent::Exit{pid,..} => {
+                self.unchanged.remove(&pid);
+                Ok(())
+            }
+            ProcEvent::Other(_) => Ok(()),
+        }
+    }
+
+    /// Serve the pending cgroup_register_unchanged_process requests
+    fn accept_unchanged(&mut self)->Result<(),std::io::Error>{
+        let listener = match &self.listener {
+            Some(listener) => listener,
+            None => return Ok(()),
+        };
+        let mut requests = Vec::new();
+        loop {
+            let stream = match listener.accept() {
+                Ok((stream,_)) => stream,
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    warn!("RulesEngine::accept_unchanged[error msg] = {:?}",e);
+                    break;
+                }
+            };
+            // a stalled or broken client must not stop the daemon
+            match Self::read_unchanged(stream) {
+                Ok(request) => requests.push(request),
+                Err(e) => warn!("RulesEngine::accept_unchanged[error msg] = {:?}",e),
+            }
+        }
+        for (pid,flags) in requests {
+            info!("RulesEngine::accept_unchanged[{}] = {:?}",pid,flags);
+            self.add_unchanged(pid,flags);
+        }
+        Ok(())
+    }
+
+    /// pid and flags sent by cgroup_register_unchanged_process
+    fn read_unchanged(mut stream:std::os::unix::net::UnixStream)->Result<(i32,UnchangeFlags),std::io::Error>{
+        use std::io::{Read,Write};
+        let timeout = Some(std::time::Duration::from_secs(1));
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(timeout)?;
+        stream.set_write_timeout(timeout)?;
+
+        let mut pid = [0u8;std::mem::size_of::<libc::pid_t>()];
+        let mut flags = [0u8;std::mem::size_of::<libc::c_int>()];
+        stream.read_exact(&mut pid)?;
+        stream.read_exact(&mut flags)?;
+        // compared with the terminating NUL by the client
+        let mut answer = CGRULE_SUCCESS_STORE_PID.as_bytes().to_vec();
+        answer.push(0);
+        stream.write_all(&answer)?;
+        Ok((
+            libc::pid_t::from_ne_bytes(pid),
+            UnchangeFlags::from_bits(libc::c_int::from_ne_bytes(flags))
+        ))
+    }
+
+    /// Handle events until an error of the connector,
+    /// errors of single processes(already exited, ...) are only logged
+    pub fn run(&mut self,connector:&ProcConnector)->Result<(),std::io::Error>{
+        let mut fds = vec![libc::pollfd{ fd:connector.as_raw_fd(), events:libc::POLLIN, revents:0 }];
+        if let Some(listener) = &self.listener {
+            fds.push(libc::pollfd{ fd:listener.as_raw_fd(), events:libc::POLLIN, revents:0 });
+        }
+        loop {
+            let ret = unsafe { libc::poll(fds.as_mut_ptr(),fds.len() as libc::nfds_t,-1) };
+            if ret < 0 {
+                let e = std::io::Error::last_os_error();
+                if e.kind() == std::io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(e);
+            }
+
+            if self.source.changed() {
+                if let Err(e) = self.reload() {
+                    error!("RulesEngine::reload[error msg] = {:?}",e);
+                }
+            }
+            if fds.len() > 1 && fds[1].revents & libc::POLLIN != 0 {
+                self.accept_unchanged()?;
+            }
+            if fds[0].revents & libc::POLLIN != 0 {
+                let events = match connector.recv() {
+                    Ok(events) => events,
+                    // events lost under load(ENOBUFS), the next ones are still delivered
+                    Err(e) if e.raw_os_error() == Some(libc::ENOBUFS)
+                        || e.kind() == std::io::ErrorKind::Interrupted
+                        || e.kind() == std::io::ErrorKind::WouldBlock => {
+                        warn!("RulesEngine::run[recv] = {:?}",e);
+                        continue;
+                    }
+                    Err(e) => return Err(e),
+                };
+                for event in events {
+                    debug!("RulesEngine::run[event] = {:?}",event);
+                    if let Err(e) = self.handle(&event) {
+                        warn!("RulesEngine::handle[{:?}] = {:?}",event.pid(),e);
+                    }
+                }
+            }
+        }
+    }
+}
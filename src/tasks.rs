@@ -0,0 +1,192 @@
+//!
+//! ### 4.Manipulation with Tasks
+//!
+//! URL: [Manipulation with Tasks](http://libcg.sourceforge.net/html/group__group__tasks.html)
+//!
+//! Functions:
+//!    - cgroup_change_cgroup_path
+//!    - cgroup_change_all_cgroups
+//!    - cgroup_get_current_controller_path
+//!    - cgroup_register_unchanged_process
+//!    - cgroup_get_procs
+//!
+//! Usage:
+//! ```
+//! use libcgroup_rs::initialization::CGroupInitializer;
+//! use libcgroup_rs::tasks::{CGroupTask,UnchangeFlags};
+//!
+//! fn main()->Result<(),Box<dyn std::error::Error>>{
+//!     CGroupInitializer::init()?;
+//!
+//!     let pid = std::process::id() as i32;
+//!
+//!     // move pid to /jobs/42 of cpu and memory hierarchies
+//!     CGroupTask::change_path("/jobs/42",pid,&["cpu","memory"])?;
+//!     // keep cgrulesengd from moving it back
+//!     CGroupTask::register_unchanged(pid,UnchangeFlags::CHILDREN)?;
+//!     println!("Current = {}",CGroupTask::current_path(pid,"cpu")?);
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+
+
+use crate::prelude::*;
+use crate::error::*;
+use log::info;
+
+
+/// Flags of cgroup_register_unchanged_process
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+pub struct UnchangeFlags(libc::c_int);
+
+impl UnchangeFlags{
+    pub const NONE:UnchangeFlags = UnchangeFlags(0);
+    /// children of the process are left unchanged too(CGROUP_DAEMON_UNCHANGE_CHILDREN)
+    pub const CHILDREN:UnchangeFlags = UnchangeFlags(0x1);
+    /// let the daemon classify the process again(CGROUP_DAEMON_CANCEL_UNCHANGE_PROCESS)
+    pub const CANCEL:UnchangeFlags = UnchangeFlags(0x2);
+
+    pub fn bits(&self)->libc::c_int{
+        self.0
+    }
+
+    pub fn from_bits(bits:libc::c_int)->Self{
+        UnchangeFlags(bits)
+    }
+
+    pub fn contains(&self,other:UnchangeFlags)->bool{
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for UnchangeFlags{
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        UnchangeFlags(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitOrAssign for UnchangeFlags{
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+
+pub struct CGroupTask;
+
+impl CGroupTask{
+
+    /// Move a pid to a group path without building the group(cgroup_change_cgroup_path),
+    /// empty controllers stand for every mounted controller
+    pub fn change_path(path:&str,pid:i32,controllers:&[&str])->Result<(),std::io::Error>{
+        let c_path = std::ffi::CString::new(path)?;
+        let c_controllers = if controllers.is_empty() {
+            vec![std::ffi::CString::new("*")?]
+        } else {
+            controllers
+                .iter()
+                .map(|ctrl| std::ffi::CString::new(*ctrl))
+                .collect::<Result<Vec<_>,_>>()?
+        };
+        let mut c_controllers_ptr:Vec<*const libc::c_char> = c_controllers
+            .iter()
+            .map(|ctrl| ctrl.as_ptr())
+            .collect();
+        c_controllers_ptr.push(std::ptr::null());
+
+        unsafe {
+            let ret = cgroup_change_cgroup_path(
+                c_path.as_ptr(),
+                pid as libc::pid_t,
+                c_controllers_ptr.as_ptr()
+            );
+            info!("CGroupTask::change_path[return code] = {}",ret);
+            if ret != C_GROUP_SUCCESS {
+                return Err(cg_get_error(ret));
+            }
+        }
+        Ok(())
+    }
+
+    /// Reclassify every process according to the cached rules(cgroup_change_all_cgroups)
+    pub fn change_all()->Result<(),std::io::Error>{
+        unsafe {
+            let ret = cgroup_change_all_cgroups();
+            info!("CGroupTask::change_all[return code] = {}",ret);
+            if ret != C_GROUP_SUCCESS {
+                return Err(cg_get_error(ret));
+            }
+        }
+        Ok(())
+    }
+
+    /// Group path of the pid in the hierarchy of the controller(cgroup_get_current_controller_path)
+    pub fn current_path(pid:i32,ctrl_name:&str)->Result<String,std::io::Error>{
+        let c_ctrl_name = std::ffi::CString::new(ctrl_name)?;
+        unsafe {
+            let mut c_path:*mut libc::c_char = std::ptr::null_mut();
+            let ret = cgroup_get_current_controller_path(
+                pid as libc::pid_t,
+                c_ctrl_name.as_ptr(),
+                &mut c_path
+            );
+            info!("CGroupTask::current_path[return code] = {}",ret);
+            if ret != C_GROUP_SUCCESS {
+                return Err(cg_get_error(ret));
+            }
+            if c_path.is_null() {
+                return Err(cg_get_error(C_EC_GROUP_NOT_EXIST));
+            }
+            let path = std::ffi::CStr::from_ptr(c_path).to_string_lossy().into_owned();
+            libc::free(c_path as *mut libc::c_void);
+            Ok(path)
+        }
+    }
+
+    /// Ask cgrulesengd to leave the pid where it is(cgroup_register_unchanged_process).
+    /// Succeeds without doing anything when the daemon isn't running,
+    /// fails when the daemon doesn't acknowledge the request
+    pub fn register_unchanged(pid:i32,flags:UnchangeFlags)->Result<(),std::io::Error>{
+        unsafe {
+            let ret = cgroup_register_unchanged_process(pid as libc::pid_t,flags.bits());
+            info!("CGroupTask::register_unchanged[return code] = {}",ret);
+            if ret != C_GROUP_SUCCESS {
+                return Err(cg_get_error(ret));
+            }
+        }
+        Ok(())
+    }
+
+    /// Process ids of the group sorted and without duplicates(cgroup_get_procs)
+    pub fn get_procs(cg_name:&str,ctrl_name:&str)->Result<Vec<i32>,std::io::Error>{
+        let c_cg_name = std::ffi::CString::new(cg_name)?;
+        let c_ctrl_name = std::ffi::CString::new(ctrl_name)?;
+        let mut pids = unsafe {
+            let mut c_pids:*mut libc::pid_t = std::ptr::null_mut();
+            let mut c_size:libc::c_int = 0;
+            let ret = cgroup_get_procs(
+                c_cg_name.as_ptr(),
+                c_ctrl_name.as_ptr(),
+                &mut c_pids,
+                &mut c_size
+            );
+            info!("CGroupTask::get_procs[return code] = {}",ret);
+            if ret != C_GROUP_SUCCESS {
+                return Err(cg_get_error(ret));
+            }
+            if c_pids.is_null() {
+                return Ok(Vec::new());
+            }
+            let pids:Vec<i32> = std::slice::from_raw_parts(c_pids,c_size.max(0) as usize).to_vec();
+            libc::free(c_pids as *mut libc::c_void);
+            pids
+        };
+        pids.sort_unstable();
+        pids.dedup();
+        Ok(pids)
+    }
+}
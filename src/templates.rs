@@ -0,0 +1,254 @@
+//!
+//! ### Template groups
+//!
+//! URL: [Configuration](http://libcg.sourceforge.net/html/group__group__config.html)
+//!
+//! Functions:
+//!    - cgroup_init_templates_cache
+//!    - cgroup_reload_cached_templates
+//!    - cgroup_config_create_template_group
+//!
+//! The group is only created in the controllers passed to `instantiate`.
+//! Without `CGFlags::USE_TEMPLATE_CACHE` libcgroup reloads /etc/cgconfig.conf
+//! for every call and the path given to `Templates::init` is ignored.
+//!
+//! Template names may contain the substitutions of cgconfig.conf:
+//!    - %u user name(uid when the name is unknown), %U uid
+//!    - %g group name(gid when the name is unknown), %G gid
+//!    - %p process name(pid when the name is unknown), %P pid
+//!
+//! Usage:
+//! ```
+//! use libcgroup_rs::initialization::CGroupInitializer;
+//! use libcgroup_rs::templates::{Templates, TemplateSubst, CGFlags};
+//!
+//! fn main()->Result<(),Box<dyn std::error::Error>>{
+//!     CGroupInitializer::init()?;
+//!
+//!     // template users/%u { cpu { cpu.shares = 500; } }
+//!     Templates::init("/etc/cgconfig.conf")?;
+//!
+//!     let subst = TemplateSubst::for_pid(std::process::id() as i32)?;
+//!     let cg = Templates::instantiate("users/%u",&subst,&["cpu"],CGFlags::USE_TEMPLATE_CACHE)?;
+//!     println!("Group = {}",cg.get_name());
+//!
+//!     cg.attach_task()?;
+//!     Ok(())
+//! }
+//! ```
+//!
+
+
+use crate::prelude::*;
+use crate::error::*;
+use crate::manipulation::CGroupBuilder;
+use crate::config::lookup_r;
+use log::info;
+
+
+/// Flags of cgroup_config_create_template_group and cgroup_change_cgroup_flags(enum cgflags)
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+pub struct CGFlags(libc::c_int);
+
+impl CGFlags{
+    pub const NONE:CGFlags = CGFlags(0);
+    /// use the cached rules(cgroup_init_rules_cache)
+    pub const USE_CACHE:CGFlags = CGFlags(0x01);
+    /// use the cached templates(cgroup_init_templates_cache)
+    pub const USE_TEMPLATE_CACHE:CGFlags = CGFlags(0x02);
+
+    pub fn bits(&self)->libc::c_int{
+        self.0
+    }
+
+    pub fn from_bits(bits:libc::c_int)->Self{
+        CGFlags(bits)
+    }
+
+    pub fn contains(&self,other:CGFlags)->bool{
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for CGFlags{
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        CGFlags(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitOrAssign for CGFlags{
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+
+
+/// Values of the %u/%U/%g/%G/%p/%P substitutions
+#[derive(Debug,Clone,PartialEq,Eq,Default)]
+pub struct TemplateSubst{
+    pub user:Option<String>,
+    pub uid:u32,
+    pub group:Option<String>,
+    pub gid:u32,
+    pub procname:Option<String>,
+    pub pid:i32,
+}
+
+impl TemplateSubst{
+
+    /// Owner and name of a running process(/proc/<pid>/status, /proc/<pid>/comm)
+    pub fn for_pid(pid:i32)->Result<Self,std::io::Error>{
+        let status = std::fs::read_to_string(format!("/proc/{}/status",pid))?;
+        let field = |name:&str| -> Result<u32,std::io::Error> {
+            status
+                .lines()
+                .find_map(|line| line.strip_prefix(name))
+                .and_then(|ids| ids.split_whitespace().nth(1))
+                .and_then(|id| id.parse().ok())
+                .ok_or_else(|| std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Missing {} in /proc/{}/status",name,pid)
+                ))
+        };
+        // effective ids, as used by cgrulesengd
+        let uid = field("Uid:")?;
+        let gid = field("Gid:")?;
+        let procname = std::fs::read_to_string(format!("/proc/{}/comm",pid))
+            .ok()
+            .map(|comm| comm.trim_end().to_string());
+
+        Ok(Self{
+            user:user_name(uid),
+            uid,
+            group:group_name(gid),
+            gid,
+            procname,
+            pid
+        })
+    }
+
+    pub fn substitute(&self,template_name:&str)->String{
+        let mut name = String::with_capacity(template_name.len());
+        let mut chars = template_name.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                name.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('u') => name.push_str(&self.user.clone().unwrap_or_else(|| self.uid.to_string())),
+                Some('U') => name.push_str(&self.uid.to_string()),
+                Some('g') => name.push_str(&self.group.clone().unwrap_or_else(|| self.gid.to_string())),
+                Some('G') => name.push_str(&self.gid.to_string()),
+                Some('p') => name.push_str(&self.procname.clone().unwrap_or_else(|| self.pid.to_string())),
+                Some('P') => name.push_str(&self.pid.to_string()),
+                Some(other) => {
+                    name.push('%');
+                    name.push(other);
+                }
+                None => name.push('%'),
+            }
+        }
+        name
+    }
+}
+
+
+fn user_name(uid:u32)->Option<String>{
+    lookup_r(|buf| unsafe {
+        let mut pw:libc::passwd = std::mem::zeroed();
+        let mut result:*mut libc::passwd = std::ptr::null_mut();
+        let ret = libc::getpwuid_r(uid,&mut pw,buf.as_mut_ptr(),buf.len(),&mut result);
+        if ret != 0 {
+            return Err(ret);
+        }
+        if result.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(std::ffi::CStr::from_ptr(pw.pw_name).to_string_lossy().into_owned()))
+    })
+}
+
+
+pub(crate) fn group_name(gid:u32)->Option<String>{
+    lookup_r(|buf| unsafe {
+        let mut gr:libc::group = std::mem::zeroed();
+        let mut result:*mut libc::group = std::ptr::null_mut();
+        let ret = libc::getgrgid_r(gid,&mut gr,buf.as_mut_ptr(),buf.len(),&mut result);
+        if ret != 0 {
+            return Err(ret);
+        }
+        if result.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(std::ffi::CStr::from_ptr(gr.gr_name).to_string_lossy().into_owned()))
+    })
+}
+
+
+
+pub struct Templates;
+
+impl Templates{
+
+    /// Load the template sections of a config(cgroup_init_templates_cache)
+    pub fn init(pathname:&str)->Result<(),std::io::Error>{
+        unsafe {
+            let c_pathname = std::ffi::CString::new(pathname)?;
+            let ret = cgroup_init_templates_cache(c_pathname.as_ptr());
+            info!("Templates::init[return code] = {}",ret);
+            if ret != C_GROUP_SUCCESS {
+                return Err(cg_get_error(ret));
+            }
+        }
+        Ok(())
+    }
+
+    /// Refresh the cache after the config changed(cgroup_reload_cached_templates)
+    pub fn reload(pathname:&str)->Result<(),std::io::Error>{
+        unsafe {
+            let c_pathname = std::ffi::CString::new(pathname)?;
+            let ret = cgroup_reload_cached_templates(c_pathname.as_ptr());
+            info!("Templates::reload[return code] = {}",ret);
+            if ret != C_GROUP_SUCCESS {
+                return Err(cg_get_error(ret));
+            }
+        }
+        Ok(())
+    }
+
+    /// Create the group of a template with substituted name in the given controllers(cgroup_config_create_template_group),
+    /// without `CGFlags::USE_TEMPLATE_CACHE` the templates are read from /etc/cgconfig.conf
+    pub fn instantiate(
+        template_name:&str,
+        subst:&TemplateSubst,
+        controllers:&[&str],
+        flags:CGFlags
+    )->Result<CGroupBuilder<'static>,std::io::Error>{
+        // before the group is allocated, an early return would leak it
+        let c_template_name = std::ffi::CString::new(template_name)?;
+        let cg = CGroupBuilder::new(subst.substitute(template_name))?;
+        for ctrl_name in controllers {
+            if let Err(e) = cg.add_controller(ctrl_name) {
+                cg.free();
+                return Err(e);
+            }
+        }
+        unsafe {
+            let ret = cgroup_config_create_template_group(
+                cg.as_ptr(),
+                c_template_name.as_ptr(),
+                flags.bits()
+            );
+            info!("Templates::instantiate[return code] = {}",ret);
+            if ret != C_GROUP_SUCCESS {
+                cg.free();
+                return Err(cg_get_error(ret));
+            }
+        }
+        Ok(cg)
+    }
+}
@@ -0,0 +1,251 @@
+//!
+//! ### Tree of groups
+//!
+//! In-memory hierarchy of a controller built from `CGroupWalkIterator`,
+//! paths of the nodes are relative to the root of the hierarchy like `CGroupBuilder` names.
+//!
+//! Usage:
+//! ```
+//! use libcgroup_rs::initialization::CGroupInitializer;
+//! use libcgroup_rs::iterators::{CGroupWalkBuilder, WalkDepth};
+//! use libcgroup_rs::tree::CGroupTree;
+//!
+//! fn main()->Result<(),Box<dyn std::error::Error>>{
+//!     CGroupInitializer::init()?;
+//!
+//!     let tree = CGroupTree::from("cpu","/")?;
+//!     for node in tree.iter() {
+//!         println!("{}{} ({} procs)","  ".repeat(node.depth),node.name,node.procs);
+//!     }
+//!
+//!     // two levels with the cpu.* values
+//!     let mut walk = CGroupWalkBuilder::new("cpu","jobs");
+//!     walk.set_depth(WalkDepth::Limited(2));
+//!     let jobs = CGroupTree::from_walk(&walk,true)?;
+//!     if let Some(node) = jobs.find("/jobs/42") {
+//!         println!("Shares = {:?}",node.values.get("cpu.shares"));
+//!     }
+//!     Ok(())
+//! }
+//! ```
+//!
+
+
+use crate::error::*;
+use crate::initialization::CGroupInitializer;
+use crate::iterators::*;
+use log::info;
+
+
+/// Group of a `CGroupTree`
+#[derive(Debug,Clone,PartialEq,Eq,Default)]
+pub struct CGroupNode{
+    /// last component of the path, empty for the root of the hierarchy
+    pub name:String,
+    /// path relative to the root of the hierarchy(`/jobs/42`)
+    pub path:String,
+    /// level below the root of the tree
+    pub depth:usize,
+    /// number of processes(cgroup.procs)
+    pub procs:usize,
+    /// number of threads(tasks or cgroup.threads)
+    pub threads:usize,
+    /// control files of the controller and their content, empty unless loaded
+    pub values:std::collections::BTreeMap<String,String>,
+    pub children:Vec<CGroupNode>,
+}
+
+impl CGroupNode{
+
+    pub fn is_leaf(&self)->bool{
+        self.children.is_empty()
+    }
+
+    pub fn get_child(&self,name:&str)->Option<&CGroupNode>{
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    /// The node and every node below it, parents first
+    pub fn iter(&self)->CGroupNodeIter<'_>{
+        CGroupNodeIter{ stack:vec![self], max_depth:None }
+    }
+
+    /// Nodes at most `max_depth` levels below the root of the tree
+    pub fn iter_depth(&self,max_depth:usize)->CGroupNodeIter<'_>{
+        CGroupNodeIter{ stack:vec![self], max_depth:Some(max_depth) }
+    }
+
+    pub fn descendants(&self)->Vec<&CGroupNode>{
+        self.iter().skip(1).collect()
+    }
+
+    /// Processes of the node and of every node below it
+    pub fn total_procs(&self)->usize{
+        self.iter().map(|node| node.procs).sum()
+    }
+}
+
+
+/// Pre-order iterator over a node and its children
+pub struct CGroupNodeIter<'a>{
+    stack:Vec<&'a CGroupNode>,
+    max_depth:Option<usize>,
+}
+
+impl<'a> Iterator for CGroupNodeIter<'a>{
+    type Item = &'a CGroupNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        if self.max_depth.map_or(true,|max_depth| node.depth < max_depth) {
+            self.stack.extend(node.children.iter().rev());
+        }
+        Some(node)
+    }
+}
+
+
+/// Groups of a controller below a base path
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct CGroupTree{
+    pub ctrl_name:String,
+    pub root:CGroupNode,
+}
+
+impl CGroupTree{
+
+    /// Every group below the base path, without values
+    pub fn from(ctrl_name:&str,base_path:&str)->Result<Self,std::io::Error>{
+        Self::from_walk(&CGroupWalkBuilder::new(ctrl_name,base_path),false)
+    }
+
+    /// Tree of the walk, the order of the builder is ignored,
+    /// values are the files of the controller(`cpu.*`) read from each group
+    pub fn from_walk(walk:&CGroupWalkBuilder,load_values:bool)->Result<Self,std::io::Error>{
+        let ctrl_name = walk.get_ctrl_name();
+        let mount_point = CGroupInitializer::get_subsys_mount_point(ctrl_name)
+            .ok_or_else(|| cg_get_error(C_EC_GROUP_SUBSYS_NOT_MOUNTED))?;
+
+        let mut pre_order = *walk;
+        pre_order.set_order(WalkOrder::Pre);
+        Self::from_entries(ctrl_name,&mount_point,pre_order.build()?,load_values)
+    }
+
+    /// Tree of pre-order entries of a hierarchy mounted at `mount_point`
+    pub fn from_entries<I:IntoIterator<Item=WalkEntry>>(
+        ctrl_name:&str,
+        mount_point:&str,
+        entries:I,
+        load_values:bool
+    )->Result<Self,std::io::Error>{
+        let mount_point = std::path::Path::new(mount_point);
+
+        // nodes whose children are still walked, with their full path
+        let mut stack:Vec<(CGroupNode,std::path::PathBuf)> = Vec::new();
+        let mut root:Option<CGroupNode> = None;
+        let value_prefix = format!("{}.",ctrl_name);
+
+        for entry in entries {
+            match entry.kind {
+                WalkEntryKind::Dir => {
+                    // close the siblings and their children
+                    while let Some((_,full_path)) = stack.last() {
+                        if entry.full_path.starts_with(full_path) && entry.full_path != *full_path {
+                            break;
+                        }
+                        Self::close(&mut stack,&mut root);
+                    }
+                    let node = Self::node(mount_point,&entry.full_path,stack.len());
+                    stack.push((node,entry.full_path));
+                }
+                WalkEntryKind::File if load_values => {
+                    let name = entry.path.to_string_lossy().into_owned();
+                    if !name.starts_with(&value_prefix) {
+                        continue;
+                    }
+                    // files may follow the children of their group, which are done then
+                    let parent = entry.full_path.parent();
+                    if !stack.iter().any(|(_,full_path)| Some(full_path.as_path()) == parent) {
+                        continue;
+                    }
+                    while stack.last().map_or(false,|(_,full_path)| Some(full_path.as_path()) != parent) {
+                        Self::close(&mut stack,&mut root);
+                    }
+                    if let Some((node,_)) = stack.last_mut() {
+                        match std::fs::read_to_string(&entry.full_path) {
+                            Ok(value) => {
+                                node.values.insert(name,value.trim_end().to_string());
+                            }
+                            // write-only files
+                            Err(e) => info!("CGroupTree::from_entries[{}] = {:?}",name,e),
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+        while !stack.is_empty() {
+            Self::close(&mut stack,&mut root);
+        }
+
+        let root = root.ok_or_else(|| cg_get_error(C_EC_GROUP_NOT_EXIST))?;
+        Ok(Self{
+            ctrl_name:ctrl_name.to_string(),
+            root
+        })
+    }
+
+    /// Attach the last opened node to its parent
+    fn close(stack:&mut Vec<(CGroupNode,std::path::PathBuf)>,root:&mut Option<CGroupNode>){
+        if let Some((node,_)) = stack.pop() {
+            match stack.last_mut() {
+                Some((parent,_)) => parent.children.push(node),
+                None => *root = Some(node),
+            }
+        }
+    }
+
+    fn node(mount_point:&std::path::Path,full_path:&std::path::Path,depth:usize)->CGroupNode{
+        let relative = full_path.strip_prefix(mount_point).unwrap_or(full_path);
+        let path = format!("/{}",relative.to_string_lossy().trim_matches('/'));
+        let count = |file:&str| {
+            CGroupProcsIterator::from_path(full_path.join(file))
+                .map(|pids| pids.count())
+                .ok()
+        };
+        CGroupNode{
+            name:relative
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            path,
+            depth,
+            procs:count("cgroup.procs").unwrap_or(0),
+            threads:count("tasks").or_else(|| count("cgroup.threads")).unwrap_or(0),
+            values:std::collections::BTreeMap::new(),
+            children:Vec::new(),
+        }
+    }
+
+    /// Node of a path relative to the root of the hierarchy
+    pub fn find(&self,path:&str)->Option<&CGroupNode>{
+        let path = format!("/{}",path.trim_matches('/'));
+        let mut node = &self.root;
+        let relative = path.strip_prefix(node.path.as_str())?;
+        if !relative.is_empty() && !relative.starts_with('/') && node.path != "/" {
+            return None;
+        }
+        for name in relative.split('/').filter(|name| !name.is_empty()) {
+            node = node.get_child(name)?;
+        }
+        Some(node)
+    }
+
+    pub fn iter(&self)->CGroupNodeIter<'_>{
+        self.root.iter()
+    }
+
+    pub fn iter_depth(&self,max_depth:usize)->CGroupNodeIter<'_>{
+        self.root.iter_depth(max_depth)
+    }
+}
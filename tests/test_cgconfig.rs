@@ -0,0 +1,174 @@
+use libcgroup_rs::cgconfig::{CGConfig, ConfigItem, Assignment, GroupBlock, ControllerBlock, PermBlock, Span};
+
+const CG_CONFIG:&str = r#"
+# mount hierarchies
+mount {
+    cpu = /sys/fs/cgroup/cpu;
+    "name=foo" = /sys/fs/cgroup/foo;
+}
+
+group daemons/www {
+    perm {
+        task {
+            uid = root;
+            gid = webmaster;
+            fperm = 770;
+        }
+        admin {
+            uid = root;
+            gid = root;
+            dperm = 775;
+            fperm = 744;
+        }
+    }
+    cpu {
+        cpu.shares = "1000";
+        cpu.rt_runtime_us = 0; # inline comment
+    }
+    memory {}
+}
+
+template users/%u {
+    cpu {
+        cpu.shares = 500;
+    }
+}
+
+default {
+    perm {
+        task { uid = root; gid = root; fperm = 660; }
+    }
+}
+
+namespace {
+    cpu = daemons;
+}
+"#;
+
+#[test]
+fn test_cgconfig_parse()->Result<(),Box<dyn std::error::Error>>{
+    let config = CGConfig::parse(CG_CONFIG)?;
+    assert_eq!(config.items.len(),5);
+
+    let mount = config.mounts().next().unwrap();
+    assert_eq!(mount.entries[1].key,"name=foo");
+    assert_eq!(mount.entries[1].value,"/sys/fs/cgroup/foo");
+    assert_eq!(mount.span.line,3);
+
+    let www = config.get_group("daemons/www").unwrap();
+    let perm = www.perm.as_ref().unwrap();
+    assert_eq!(perm.task.as_ref().unwrap().gid.as_deref(),Some("webmaster"));
+    assert_eq!(perm.admin.as_ref().unwrap().dperm.as_deref(),Some("775"));
+    let cpu = www.get_controller("cpu").unwrap();
+    assert_eq!(cpu.params[0],Assignment{ span:cpu.params[0].span,..Assignment::new("cpu.shares","1000") });
+    assert_eq!(cpu.params[1].span.line,24);
+    assert!(www.get_controller("memory").unwrap().params.is_empty());
+
+    assert_eq!(config.templates().next().unwrap().name,"users/%u");
+    assert_eq!(config.default_perm().unwrap().perm.task.as_ref().unwrap().fperm.as_deref(),Some("660"));
+    assert!(matches!(config.items[4],ConfigItem::Namespace(_)));
+
+    Ok(())
+}
+
+
+#[test]
+fn test_cgconfig_errors(){
+    let e = CGConfig::parse("group foo {\n    cpu {\n        cpu.shares 100;\n    }\n}").unwrap_err();
+    assert_eq!((e.span.line,e.span.column),(3,20));
+    assert_eq!(e.to_string(),"3:20: Expected '=', found '100'");
+
+    let e = CGConfig::parse("group foo {\n    perm { task { owner = root; } }\n}").unwrap_err();
+    assert_eq!((e.span.line,e.span.column),(2,19));
+
+    let e = CGConfig::parse("group foo { perm { task { fperm = 999; } } }").unwrap_err();
+    assert!(e.message.contains("Invalid mode"));
+
+    let e = CGConfig::parse("groups foo {}").unwrap_err();
+    assert_eq!(e.to_string(),"1:1: Unknown block 'groups'");
+
+    let e = CGConfig::parse("group foo { cpu { cpu.shares = \"100; } }").unwrap_err();
+    assert!(e.message.contains("Unterminated"));
+
+    assert!(CGConfig::parse("group foo { cpu {} ").is_err());
+    assert!(CGConfig::parse("group foo { cpu {} cpu {} }").is_err());
+}
+
+
+/// Same config with every span reset, positions change when the config is printed
+fn without_spans(mut config:CGConfig)->CGConfig{
+    fn clear_perm(perm:&mut PermBlock){
+        perm.span = Span::default();
+        if let Some(task) = perm.task.as_mut() {
+            task.span = Span::default();
+        }
+        if let Some(admin) = perm.admin.as_mut() {
+            admin.span = Span::default();
+        }
+    }
+    fn clear_assignments(entries:&mut [Assignment]){
+        entries.iter_mut().for_each(|a| a.span = Span::default());
+    }
+
+    for item in config.items.iter_mut() {
+        match item {
+            ConfigItem::Mount(m) => {
+                m.span = Span::default();
+                clear_assignments(&mut m.entries);
+            }
+            ConfigItem::Namespace(n) => {
+                n.span = Span::default();
+                clear_assignments(&mut n.entries);
+            }
+            ConfigItem::Group(g) | ConfigItem::Template(g) => {
+                g.span = Span::default();
+                if let Some(perm) = g.perm.as_mut() {
+                    clear_perm(perm);
+                }
+                for ctrl in g.controllers.iter_mut() {
+                    ctrl.span = Span::default();
+                    clear_assignments(&mut ctrl.params);
+                }
+            }
+            ConfigItem::Default(d) => {
+                d.span = Span::default();
+                clear_perm(&mut d.perm);
+            }
+        }
+    }
+    config
+}
+
+
+#[test]
+fn test_cgconfig_round_trip()->Result<(),Box<dyn std::error::Error>>{
+    let config = CGConfig::parse(CG_CONFIG)?;
+    let reparsed = CGConfig::parse(&config.to_string())?;
+    assert_eq!(without_spans(reparsed),without_spans(config));
+
+    let mut generated = CGConfig::default();
+    generated.items.push(ConfigItem::Group(GroupBlock{
+        name:String::from("jobs/42"),
+        controllers:vec![ControllerBlock{
+            name:String::from("cpuset"),
+            params:vec![Assignment::new("cpuset.cpus","0-3"),Assignment::new("cpuset.mems","")],
+            ..ControllerBlock::default()
+        }],
+        ..GroupBlock::default()
+    }));
+    let printed = generated.to_string();
+    assert_eq!(printed,"group jobs/42 {\n\tcpuset {\n\t\tcpuset.cpus = 0-3;\n\t\tcpuset.mems = \"\";\n\t}\n}\n");
+    let reparsed = CGConfig::parse(&printed)?;
+    assert_eq!(without_spans(reparsed),generated);
+
+    // a backslash is kept as in libcgroup, values with `\` or `"` are not written
+    let config = CGConfig::parse(r#"group foo { cpu { cpu.x = "a\b c"; } }"#)?;
+    assert_eq!(config.get_group("foo").unwrap().controllers[0].params[0].value,"a\\b c");
+    assert!(CGConfig::parse(r#"group foo { cpu { cpu.x = "a\"b"; } }"#).is_err());
+    let pathname = std::env::temp_dir().join("libcgroup_rs_quote.conf");
+    let e = config.write_file(pathname.to_str().unwrap()).unwrap_err();
+    assert_eq!(e.kind(),std::io::ErrorKind::InvalidInput);
+    assert!(!pathname.exists());
+
+    Ok(())
+}
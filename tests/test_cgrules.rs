@@ -0,0 +1,120 @@
+use libcgroup_rs::cgrules::{CGRules, ProcessInfo, RuleUser, RulesCache};
+
+const CG_RULES:&str = "
+# <user>[:<process>]  <controllers>  <destination>  [<options>]
+student             cpu,memory      usergroup/student/
+%                   devices         usergroup/student/devices/
+@admin              cpu             admingroup/
+peter:cp            *               copies/%u/%p/
+*:/usr/bin/backup   blkio           backup/%U/       ignore
+*                   cpu             default/
+";
+
+fn process(user:&str,groups:&[&str],exe:&str)->ProcessInfo{
+    ProcessInfo{
+        pid:42,
+        uid:1000,
+        gid:1000,
+        user:Some(user.to_string()),
+        groups:groups.iter().map(|g| g.to_string()).collect(),
+        procname:None,
+        exe:Some(exe.to_string()),
+        realtime:false,
+    }
+}
+
+
+#[test]
+fn test_cgrules_parse()->Result<(),Box<dyn std::error::Error>>{
+    let rules = CGRules::parse(CG_RULES)?;
+    assert_eq!(rules.rules.len(),5);
+
+    let student = &rules.rules[0];
+    assert_eq!(student.user,RuleUser::User(String::from("student")));
+    assert_eq!(student.targets.len(),2);
+    assert_eq!(student.targets[0].controllers,vec!["cpu","memory"]);
+    assert_eq!(student.targets[1].span.line,4);
+
+    assert_eq!(rules.rules[1].user,RuleUser::Group(String::from("admin")));
+    assert_eq!(rules.rules[2].procname.as_deref(),Some("cp"));
+    assert!(rules.rules[3].is_ignore());
+
+    let printed = rules.to_string();
+    assert_eq!(CGRules::parse(&printed)?,CGRules::parse(&CGRules::parse(&printed)?.to_string())?);
+    Ok(())
+}
+
+
+#[test]
+fn test_cgrules_errors(){
+    let e = CGRules::parse("student cpu").unwrap_err();
+    assert_eq!(e.span.line,1);
+    assert!(CGRules::parse("% cpu foo/").is_err());
+    assert!(CGRules::parse("a cpu a/\n%:cp cpu b/").is_err());
+    let e = CGRules::parse("\n  a  cpu  a/  sticky").unwrap_err();
+    assert_eq!((e.span.line,e.span.column),(2,15));
+    assert!(CGRules::parse("@ cpu a/").is_err());
+
+    let rules = CGRules::parse("a cpu a/ ignore_rt\nb cpu b/ ignore,ignore_rt").unwrap();
+    assert_eq!(rules.rules[0].targets[0].options,vec!["ignore_rt"]);
+    assert!(!rules.rules[0].is_ignore());
+    assert!(rules.rules[1].is_ignore());
+    assert!(rules.rules[0].is_ignore_rt());
+}
+
+
+#[test]
+fn test_cgrules_classify()->Result<(),Box<dyn std::error::Error>>{
+    let rules = CGRules::parse(CG_RULES)?;
+
+    let m = rules.classify(&process("student",&["students"],"/bin/sh")).unwrap();
+    assert_eq!(m.destinations.get("cpu").map(String::as_str),Some("usergroup/student/"));
+    assert_eq!(m.destinations.get("devices").map(String::as_str),Some("usergroup/student/devices/"));
+    assert!(!m.ignore);
+
+    let m = rules.classify(&process("alice",&["users","admin"],"/bin/sh")).unwrap();
+    assert_eq!(m.destinations.len(),1);
+    assert_eq!(m.destinations["cpu"],"admingroup/");
+
+    let m = rules.classify(&process("peter",&["users"],"/bin/cp")).unwrap();
+    assert_eq!(m.destinations["*"],"copies/peter/42/");
+
+    let m = rules.classify(&process("bob",&["users"],"/usr/bin/backup")).unwrap();
+    assert_eq!(m.destinations["blkio"],"backup/1000/");
+    assert!(m.ignore);
+
+    let m = rules.classify(&process("bob",&["users"],"/bin/cp")).unwrap();
+    assert_eq!(m.destinations["cpu"],"default/");
+
+    // ignore_rt leaves only realtime processes where they are
+    let rt_rules = CGRules::parse("student cpu rt/ ignore_rt\n* cpu default/")?;
+    let mut student = process("student",&[],"/bin/sh");
+    assert!(!rt_rules.classify(&student).unwrap().ignore);
+    student.realtime = true;
+    let m = rt_rules.classify(&student).unwrap();
+    assert_eq!(m.destinations["cpu"],"rt/");
+    assert!(m.ignore);
+
+    let strict = CGRules::parse("student cpu a/")?;
+    assert!(strict.classify(&process("bob",&[],"/bin/sh")).is_none());
+    Ok(())
+}
+
+
+#[test]
+fn test_process_info()->Result<(),Box<dyn std::error::Error>>{
+    let process = ProcessInfo::for_pid(std::process::id() as i32)?;
+    println!("Process = {:?}",process);
+    assert!(process.exe.is_some());
+    assert!(!process.realtime);
+    Ok(())
+}
+
+
+#[test]
+fn test_rules_config()->Result<(),Box<dyn std::error::Error>>{
+    for rule in RulesCache::rules_config()?.rules.iter() {
+        println!("Rule = {:?}",rule);
+    }
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use libcgroup_rs::config::{Loader, Severity, SystemHierarchy, DefaultPermissions, resolve_uid, resolve_gid};
+use libcgroup_rs::cgconfig::CGConfig;
+use libcgroup_rs::manipulation::DeleteFlags;
+
+fn write_config(name:&str,content:&str)->Result<String,std::io::Error>{
+    let path = std::env::temp_dir().join(name);
+    std::fs::write(&path,content)?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+
+const VALIDATE_CONFIG:&str = r#"
+mount {
+    memory = /dev/shm/libcgroup_rs_not_mounted;
+}
+
+group foo {
+    perm {
+        task { uid = root; gid = no_such_group_libcgroup_rs; }
+        admin { uid = 0; gid = 0; }
+    }
+    cpu {
+        cpu.shares = abc;
+        cpu.no_such_param = 1;
+        other.param = 1;
+    }
+    memory {
+        memory.limit_in_bytes = 1;
+    }
+    no_such_controller {
+        no_such_controller.value = 1;
+    }
+    blkio {
+        blkio.weight = 1;
+    }
+}
+
+template users/%u {
+    perm {
+        task { uid = %u; gid = %g; }
+    }
+}
+"#;
+
+
+#[test]
+fn test_validate()->Result<(),Box<dyn std::error::Error>>{
+    // cpu mounted with its files, memory mounted by the config only, blkio not mounted
+    let cpu = std::env::temp_dir().join("libcgroup_rs_validate_cpu");
+    std::fs::create_dir_all(&cpu)?;
+    std::fs::write(cpu.join("cgroup.procs"),"")?;
+    std::fs::write(cpu.join("cpu.shares"),"1024")?;
+    let cpu_path = cpu.to_string_lossy().into_owned();
+    let mut system = SystemHierarchy::new();
+    system
+        .add_controller("cpu",Some(&cpu_path))
+        .add_controller("memory",None)
+        .add_controller("blkio",None);
+
+    let config = CGConfig::parse(VALIDATE_CONFIG)?;
+    let diagnostics:Vec<_> = Loader::validate_with(&config,&system)
+        .into_iter()
+        .map(|d| (d.severity,d.span.line,d.message))
+        .collect();
+    let expected = vec![
+        (Severity::Error,8,String::from("Unknown group 'no_such_group_libcgroup_rs'")),
+        (Severity::Error,12,String::from("Value of 'cpu.shares' is not a number = abc")),
+        (Severity::Error,13,format!("Parameter 'cpu.no_such_param' not found under {}",cpu_path)),
+        (Severity::Warning,14,String::from("Parameter 'other.param' does not belong to controller 'cpu'")),
+        (Severity::Error,14,format!("Parameter 'other.param' not found under {}",cpu_path)),
+        (Severity::Error,19,String::from("Unknown controller 'no_such_controller'")),
+        (Severity::Error,22,String::from("Controller 'blkio' is not mounted")),
+    ];
+    assert_eq!(diagnostics,expected);
+
+    std::fs::remove_dir_all(cpu)?;
+    Ok(())
+}
+
+
+#[test]
+fn test_validate_system()->Result<(),Box<dyn std::error::Error>>{
+    let pathname = write_config("libcgroup_rs_validate.conf",VALIDATE_CONFIG)?;
+
+    // the result depends on the machine, the owner check does not
+    let diagnostics = Loader::validate(&pathname)?;
+    for d in diagnostics.iter() {
+        println!("{}",d);
+    }
+    assert!(diagnostics.iter().any(|d| d.is_error() && d.message.contains("no_such_group_libcgroup_rs")));
+    assert!(diagnostics.iter().any(|d| d.message == "Unknown controller 'no_such_controller'"));
+
+    std::fs::remove_file(pathname)?;
+    Ok(())
+}
+
+
+#[test]
+fn test_validate_parse_error()->Result<(),Box<dyn std::error::Error>>{
+    let pathname = write_config("libcgroup_rs_validate_parse.conf","group foo {\n  cpu {\n")?;
+
+    let diagnostics = Loader::validate(&pathname)?;
+    assert_eq!(diagnostics.len(),1);
+    assert!(diagnostics[0].is_error());
+    println!("{}",diagnostics[0]);
+
+    std::fs::remove_file(pathname)?;
+    Ok(())
+}
+
+
+#[test]
+fn test_unload_missing_file(){
+    let flags = DeleteFlags::IGNORE_MIGRATION | DeleteFlags::RECURSIVE;
+    assert!(flags.contains(DeleteFlags::RECURSIVE));
+    assert!(Loader::unload_file("/dev/shm/libcgroup_rs_no_such.conf",flags).is_err());
+}
+
+
+#[test]
+fn test_default_from_perm()->Result<(),Box<dyn std::error::Error>>{
+    let config = CGConfig::parse(r#"
+default {
+    perm {
+        task { uid = root; gid = 0; fperm = 664; }
+        admin { uid = 0; dperm = 775; fperm = 644; }
+    }
+}
+"#)?;
+    let perms = DefaultPermissions::from_perm(&config.default_perm().unwrap().perm)?;
+    assert_eq!(perms,DefaultPermissions{
+        tasks_uid:Some(0),
+        tasks_gid:Some(0),
+        admin_uid:Some(0),
+        admin_gid:None,
+        dir_mode:Some(0o775),
+        file_mode:Some(0o644),
+        tasks_mode:Some(0o664),
+    });
+
+    let config = CGConfig::parse("default { perm { task { uid = no_such_user_libcgroup_rs; } } }")?;
+    assert!(DefaultPermissions::from_perm(&config.default_perm().unwrap().perm).is_err());
+    Ok(())
+}
+
+
+#[test]
+fn test_resolve_ids(){
+    assert_eq!(resolve_uid("root"),Some(0));
+    assert_eq!(resolve_gid("root"),Some(0));
+    assert_eq!(resolve_uid("1234"),Some(1234));
+    assert_eq!(resolve_uid("no_such_user_libcgroup_rs"),None);
+    assert_eq!(resolve_gid("no_such_group_libcgroup_rs"),None);
+}
@@ -0,0 +1,132 @@
+use libcgroup_rs::initialization::CGroupInitializer;
+use libcgroup_rs::manipulation::CGroupBuilder;
+use libcgroup_rs::diff::{CGroupDiff, ChangeKind, ControllerValues};
+use libcgroup_rs::tree::{CGroupTree, CGroupNode};
+
+
+fn values(entries:&[(&str,&str,&str)])->ControllerValues{
+    let mut values = ControllerValues::new();
+    for (ctrl,name,value) in entries.iter() {
+        let ctrl_values = values.entry(ctrl.to_string()).or_default();
+        if !name.is_empty() {
+            ctrl_values.insert(name.to_string(),value.to_string());
+        }
+    }
+    values
+}
+
+fn node(path:&str,depth:usize,shares:&str,children:Vec<CGroupNode>)->CGroupNode{
+    let mut node = CGroupNode{
+        name:path.rsplit('/').next().unwrap_or("").to_string(),
+        path:path.to_string(),
+        depth,
+        children,
+        ..Default::default()
+    };
+    node.values.insert(String::from("cpu.shares"),shares.to_string());
+    node
+}
+
+
+#[test]
+fn test_diff_values(){
+    let old = values(&[
+        ("cpu","cpu.shares","1024"),
+        ("cpu","cpu.cfs_quota_us","-1"),
+        ("memory","memory.limit_in_bytes","1G"),
+    ]);
+    let new = values(&[
+        ("cpu","cpu.shares","512"),
+        ("cpu","cpu.cfs_quota_us","-1"),
+        ("cpuset","cpuset.cpus","0-1"),
+    ]);
+    let diff = CGroupDiff::between_values("foo",&old,&new);
+    let kinds:Vec<ChangeKind> = diff.changes.iter().map(|c| c.kind.clone()).collect();
+    assert_eq!(kinds,vec![
+        ChangeKind::RemoveValue{
+            controller:String::from("memory"),
+            name:String::from("memory.limit_in_bytes"),
+            value:String::from("1G")
+        },
+        ChangeKind::RemoveController(String::from("memory")),
+        ChangeKind::ChangeValue{
+            controller:String::from("cpu"),
+            name:String::from("cpu.shares"),
+            old:String::from("1024"),
+            new:String::from("512")
+        },
+        ChangeKind::AddController(String::from("cpuset")),
+        ChangeKind::AddValue{
+            controller:String::from("cpuset"),
+            name:String::from("cpuset.cpus"),
+            value:String::from("0-1")
+        },
+    ]);
+    assert_eq!(diff.to_string().lines().nth(2),Some("~ foo cpu.shares: 1024 -> 512"));
+    assert!(CGroupDiff::between_values("foo",&old,&old).is_empty());
+
+    // only what new sets
+    let diff = CGroupDiff::between_set("foo",&old,&new);
+    let plan:Vec<String> = diff.changes.iter().map(|c| c.to_string()).collect();
+    assert_eq!(plan,vec![
+        "~ foo cpu.shares: 1024 -> 512",
+        "+ foo cpuset",
+        "+ foo cpuset.cpus = 0-1",
+    ]);
+}
+
+
+#[test]
+fn test_diff_trees(){
+    let old = CGroupTree{
+        ctrl_name:String::from("cpu"),
+        root:node("/",0,"1024",vec![
+            node("/a",1,"1024",vec![node("/a/x",2,"1024",vec![])]),
+            node("/b",1,"1024",vec![]),
+        ]),
+    };
+    let new = CGroupTree{
+        ctrl_name:String::from("cpu"),
+        root:node("/",0,"1024",vec![
+            node("/b",1,"512",vec![node("/b/y",2,"256",vec![])]),
+        ]),
+    };
+
+    let diff = CGroupDiff::between_trees(&old,&new);
+    let plan:Vec<String> = diff.changes.iter().map(|c| c.to_string()).collect();
+    assert_eq!(plan,vec![
+        "- /a/x",
+        "- /a",
+        "~ /b cpu.shares: 1024 -> 512",
+        "+ /b/y",
+        "+ /b/y cpu",
+        "+ /b/y cpu.shares = 256",
+    ]);
+    assert_eq!(diff.get_group("/b/y").len(),3);
+    assert!(CGroupDiff::between_trees(&new,&new).is_empty());
+
+    // nodes built without values
+    let mut bare = new.clone();
+    bare.root.children[0].values.clear();
+    let diff = CGroupDiff::between_trees(&bare,&new);
+    assert!(diff.is_empty());
+    assert!(CGroupDiff::between_trees(&new,&bare).is_empty());
+}
+
+
+#[test]
+fn test_diff_groups()->Result<(),Box<dyn std::error::Error>>{
+    CGroupInitializer::init()?;
+
+    let current = CGroupBuilder::new("foo")?;
+    let cpu = current.add_controller("cpu")?;
+    cpu.add_u64("cpu.shares",1024)?;
+    cpu.add_i64("cpu.cfs_quota_us",-1)?;
+    let desired = CGroupBuilder::new("foo")?;
+    desired.add_controller("cpu")?.add_u64("cpu.shares",512)?;
+
+    let diff = CGroupDiff::between(&current,&desired)?;
+    assert_eq!(diff.len(),1);
+    println!("{}",diff);
+    Ok(())
+}
@@ -0,0 +1,215 @@
+use libcgroup_rs::extend::{CGroupMount, CGroupVersion, Propagation, MountBuilder, CGroup2Option, ProcCGroup, MountFlags, UmountFlags, ProcessCGroups, FsError};
+
+const MOUNT_INFO:&str = "\
+22 1 8:1 / / rw,relatime shared:1 - ext4 /dev/sda1 rw
+25 22 0:23 / /sys/fs/cgroup rw,nosuid,nodev,noexec,relatime shared:9 - cgroup2 cgroup2 rw,nsdelegate,memory_recursiveprot
+31 25 0:27 / /sys/fs/cgroup/cpu,cpuacct rw,nosuid,nodev,noexec,relatime shared:12 - cgroup cgroup rw,cpu,cpuacct
+32 25 0:28 / /sys/fs/cgroup/systemd rw,nosuid,nodev,noexec,relatime master:5 propagate_from:2 - cgroup cgroup rw,xattr,name=systemd
+40 22 0:29 /foo /dev/shm/my\\040cgroups rw,relatime - cgroup none rw,release_agent=/bin/true,name=foo
+";
+
+#[test]
+fn test_mountinfo_parse()->Result<(),Box<dyn std::error::Error>>{
+    let mounts = CGroupMount::parse(MOUNT_INFO)?;
+    assert_eq!(mounts.len(),4);
+
+    let v2 = &mounts[0];
+    assert_eq!(v2.version,CGroupVersion::V2);
+    assert_eq!(v2.mount_point,"/sys/fs/cgroup");
+    assert_eq!(v2.propagation,vec![Propagation::Shared(9)]);
+    assert!(v2.controllers.is_empty());
+    assert_eq!(v2.super_opts,vec!["rw","nsdelegate","memory_recursiveprot"]);
+
+    let cpu = &mounts[1];
+    assert_eq!(cpu.version,CGroupVersion::V1);
+    assert_eq!(cpu.controllers,vec!["cpu","cpuacct"]);
+    assert!(cpu.has_controller("cpuacct"));
+    assert_eq!(cpu.name,None);
+
+    let systemd = &mounts[2];
+    assert!(systemd.controllers.is_empty());
+    assert_eq!(systemd.name.as_deref(),Some("systemd"));
+    assert_eq!(systemd.propagation,vec![Propagation::Master(5),Propagation::PropagateFrom(2)]);
+    assert!(!systemd.is_shared());
+
+    let named = &mounts[3];
+    assert_eq!(named.root,"/foo");
+    assert_eq!(named.mount_point,"/dev/shm/my cgroups");
+    assert_eq!(named.source,"none");
+    assert_eq!(named.name.as_deref(),Some("foo"));
+    assert!(named.controllers.is_empty());
+    assert!(named.propagation.is_empty());
+
+    Ok(())
+}
+
+
+#[test]
+fn test_mountinfo_invalid(){
+    assert!(CGroupMount::parse_line("25 22 0:23 / /sys/fs/cgroup rw").is_err());
+    assert!(CGroupMount::parse_line("x 22 0:23 / /sys/fs/cgroup rw - cgroup2 cgroup2 rw").is_err());
+    assert!(CGroupMount::parse_line("").unwrap().is_none());
+}
+
+
+#[test]
+fn test_mountinfo_self()->Result<(),Box<dyn std::error::Error>>{
+    for m in CGroupMount::from_self()? {
+        println!("{:?} {} {:?}",m.version,m.mount_point,m.controllers);
+    }
+    Ok(())
+}
+
+
+#[test]
+fn test_mount_builder_typed()->Result<(),Box<dyn std::error::Error>>{
+    let v2 = MountBuilder::cgroup2(
+        "/dev/shm/cgroup2",
+        &[CGroup2Option::NsDelegate,CGroup2Option::MemoryRecursiveProt]
+    );
+    assert_eq!(v2.get_opts(),"nsdelegate,memory_recursiveprot");
+    v2.validate()?;
+
+    let v1 = MountBuilder::cgroup_v1("/dev/shm/cgroups",&["cpu","cpuacct"]);
+    assert_eq!(v1.get_opts(),"cpu,cpuacct");
+
+    let named = MountBuilder::named("/dev/shm/foo","foo");
+    assert_eq!(named.get_opts(),"none,name=foo");
+    named.validate()?;
+
+    assert!(MountBuilder::named("/dev/shm/foo","").validate().is_err());
+    assert!(MountBuilder::cgroup_v1("/dev/shm/cgroups",&["no_such_controller"]).validate().is_err());
+
+    // every controller is bound to the hierarchy
+    MountBuilder::cgroup_v1("/dev/shm/cgroups",&[]).validate()?;
+    MountBuilder::cgroup_v1("/dev/shm/cgroups",&["all"]).validate()?;
+    MountBuilder::cgroup_v1("/dev/shm/cgroups",&["all","xattr"]).validate()?;
+    assert!(MountBuilder::cgroup_v1("/dev/shm/cgroups",&["none"]).validate().is_err());
+
+    let mut unknown = MountBuilder::new();
+    unknown
+        .set_target_path("/dev/shm/cgroup2")
+        .set_src_path("none")
+        .set_type_name("cgroup2")
+        .set_opts("cpu");
+    assert!(unknown.validate().is_err());
+
+    Ok(())
+}
+
+
+#[test]
+fn test_proc_cgroups()->Result<(),Box<dyn std::error::Error>>{
+    let subsys = ProcCGroup::parse("\
+#subsys_name\thierarchy\tnum_cgroups\tenabled
+cpuset\t0\t1\t1
+cpu\t3\t64\t1
+memory\t0\t1\t0
+")?;
+    assert_eq!(subsys.len(),3);
+    assert_eq!(subsys[1].subsys_name,"cpu");
+    assert_eq!(subsys[1].hierarchy,3);
+    assert_eq!(subsys[1].num_cgroups,64);
+    assert!(!subsys[2].enabled);
+
+    assert!(ProcCGroup::parse("cpu 3 64").is_err());
+    Ok(())
+}
+
+
+#[test]
+fn test_mount_flags(){
+    let mut flags = MountFlags::NOSUID | MountFlags::NODEV;
+    flags |= MountFlags::NOEXEC;
+    assert!(flags.contains(MountFlags::NOSUID | MountFlags::NOEXEC));
+    assert!(!flags.contains(MountFlags::RDONLY));
+    assert_eq!(flags.bits(),libc::MS_NOSUID | libc::MS_NODEV | libc::MS_NOEXEC);
+
+    let umount_flags = UmountFlags::DETACH | UmountFlags::NOFOLLOW;
+    assert_eq!(umount_flags.bits(),libc::MNT_DETACH | libc::UMOUNT_NOFOLLOW);
+    assert_eq!(UmountFlags::default(),UmountFlags::NONE);
+
+    #[allow(deprecated)]
+    {
+        use libcgroup_rs::extend::{MNT_DEFAULT, MNT_DETACH};
+        assert_eq!(MNT_DEFAULT,UmountFlags::NONE);
+        assert_eq!(MNT_DETACH,UmountFlags::DETACH);
+    }
+}
+
+
+#[test]
+fn test_umount_errno()->Result<(),Box<dyn std::error::Error>>{
+    let target = std::env::temp_dir().join("libcgroup_rs_not_mounted");
+    std::fs::create_dir_all(&target)?;
+
+    let mut space = MountBuilder::new();
+    space.set_target_path(target.to_str().unwrap());
+    let e = space.umount(UmountFlags::NONE).unwrap_err();
+    println!("Umount Error = {:?}",e);
+    assert!(matches!(e.raw_os_error(),Some(code) if code == libc::EINVAL || code == libc::EPERM));
+
+    std::fs::remove_dir(&target)?;
+    Ok(())
+}
+
+
+#[test]
+fn test_mount_fs_context(){
+    let target = std::env::temp_dir().join("libcgroup_rs_fs_context");
+    let mut space = MountBuilder::new();
+    space
+        .set_target_path(target.to_str().unwrap())
+        .set_src_path("none")
+        .set_type_name("tmpfs")
+        .set_opts("no_such_option=1")
+        .set_fs_context(true);
+
+    // EINVAL with the kernel log as root, EPERM from fsopen otherwise
+    let e = space.mount().unwrap_err();
+    println!("Mount Error = {}",e);
+    assert!(matches!(FsError::errno_of(&e),Some(code) if code == libc::EINVAL || code == libc::EPERM));
+    if let Some(fs) = e.get_ref().and_then(|inner| inner.downcast_ref::<FsError>()) {
+        assert_eq!(fs.errno,libc::EINVAL);
+        assert!(fs.log.iter().any(|message| message.contains("no_such_option")));
+    }
+    assert!(!target.exists());
+}
+
+
+#[test]
+fn test_process_cgroups_parse()->Result<(),Box<dyn std::error::Error>>{
+    let cgroups = ProcessCGroups::parse("\
+12:cpu,cpuacct:/user.slice
+11:memory:/user.slice/user-1000.slice
+1:name=systemd:/user.slice/session-2.scope
+0::/odd:path
+")?;
+    assert_eq!(cgroups.entries.len(),4);
+    assert_eq!(cgroups.entries[0].controllers,vec!["cpu","cpuacct"]);
+    assert_eq!(cgroups.entries[2].name.as_deref(),Some("systemd"));
+    assert!(cgroups.entries[3].is_unified());
+
+    assert_eq!(cgroups.get_path("cpuacct"),Some("/user.slice"));
+    assert_eq!(cgroups.get_path("name=systemd"),Some("/user.slice/session-2.scope"));
+    assert_eq!(cgroups.get_path("name=foo"),None);
+    // not on a v1 hierarchy, so on cgroup2
+    assert_eq!(cgroups.get_path("pids"),Some("/odd:path"));
+    assert_eq!(cgroups.unified(),Some("/odd:path"));
+
+    let paths = cgroups.paths();
+    assert_eq!(paths.get("memory").map(|p| p.as_str()),Some("/user.slice/user-1000.slice"));
+    assert!(paths.contains_key("name=systemd"));
+
+    assert!(ProcessCGroups::parse("x:cpu:/").is_err());
+    assert!(ProcessCGroups::parse("3:cpu").is_err());
+    Ok(())
+}
+
+
+#[test]
+fn test_process_cgroups_self()->Result<(),Box<dyn std::error::Error>>{
+    let cgroups = ProcessCGroups::for_pid(std::process::id() as i32)?;
+    assert!(!cgroups.entries.is_empty());
+    Ok(())
+}
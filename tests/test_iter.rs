@@ -1,5 +1,5 @@
 use libcgroup_rs::initialization::CGroupInitializer;
-use libcgroup_rs::iterators::{CGroupAllControllerIterator, CGroupControllerIterator, CGroupTaskIterator, CGroupStatsIterator, CGroupWalkIterator};
+use libcgroup_rs::iterators::{CGroupAllControllerIterator, CGroupControllerIterator, CGroupTaskIterator, CGroupStatsIterator, CGroupWalkIterator, CGroupSubsysMountPointIterator, CGroupProcsIterator, TaskKind, CGroupWalkBuilder, WalkOrder, WalkDepth};
 
 #[test]
 fn test_all_ctrl()->Result<(),Box<dyn std::error::Error>>{
@@ -128,18 +128,113 @@ fn test_walk()->Result<(),Box<dyn std::error::Error>>{
     //println!("CTRL = {:?}",ctrl);
 
     println!("============== Walk ===============");
-    let _walk_iter = CGroupWalkIterator::from("cpu","/dev/shm/cgroups",1)?;
+    let mut walk_iter = CGroupWalkIterator::from("cpu","/",1)?;
 
-    //for (level,_info) in walk_iter.into_iter() {
-    //    println!("Level = {}",level);
-    //    println!("--------------------------------------------");
-    //}
+    for entry in walk_iter.by_ref() {
+        println!("Kind = {:?}",entry.kind);
+        println!("Path = {}",entry.full_path.display());
+        println!("Depth = {}",entry.depth);
+        println!("--------------------------------------------");
+    }
+    println!("=============================================");
+
+    // the walk is ended once
+    assert!(walk_iter.next().is_none());
+    assert!(walk_iter.next().is_none());
+    drop(walk_iter);
+
+    // stopped early, the handle is released on drop
+    let mut walk_iter = CGroupWalkIterator::from("cpu","/",0)?;
+    let base = walk_iter.next().unwrap();
+    assert_eq!(base.depth,0);
+    drop(walk_iter);
+
+
+
+    Ok(())
+}
+
+
+#[test]
+fn test_mount_point()->Result<(),Box<dyn std::error::Error>>{
+    CGroupInitializer::init()?;
+
+    println!("============== Mount Point ===============");
+    let mount_iter = CGroupSubsysMountPointIterator::from("cpu")?;
+    for path in mount_iter.into_iter() {
+        println!("Path = {}",path);
+        println!("--------------------------------------------");
+    }
+    println!("=============================================");
+
+    Ok(())
+}
+
+
+#[test]
+fn test_procs()->Result<(),Box<dyn std::error::Error>>{
+    let dir = std::env::temp_dir().join(format!("cg_procs_{}",std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join("cgroup.procs"),"12\n7\n\n12\n3\n")?;
+
+    let pids:Vec<i32> = CGroupProcsIterator::from_path(dir.join("cgroup.procs"))?.collect();
+    assert_eq!(pids,vec![12,7,12,3]);
+    std::fs::remove_dir_all(&dir)?;
+
+    CGroupInitializer::init()?;
+    println!("============== Procs ===============");
+    for pid in CGroupProcsIterator::from("/","cpu")? {
+        println!("Pid = {}",pid);
+    }
     println!("=============================================");
+    Ok(())
+}
 
 
+#[test]
+fn test_walk_tasks()->Result<(),Box<dyn std::error::Error>>{
+    CGroupInitializer::init()?;
 
+    println!("============== Tasks of subtree ===============");
+    let procs = CGroupWalkIterator::from("cpu","/",0)?.tasks(TaskKind::Procs)?;
+    let threads = CGroupWalkIterator::from("cpu","/",0)?.tasks(TaskKind::Threads)?;
+    for (path,pid) in procs.iter() {
+        println!("{} = {}",path,pid);
+    }
+    assert!(threads.len() >= procs.len());
+    println!("=============================================");
     Ok(())
 }
 
 
+#[test]
+fn test_walk_options(){
+    assert_eq!(WalkOrder::Pre.bits(),0x1);
+    assert_eq!(WalkOrder::Post.bits(),0x2);
+    assert_eq!(WalkDepth::Unbounded.as_raw(),0);
+    assert_eq!(WalkDepth::Limited(2).as_raw(),2);
+    assert_eq!(WalkDepth::from_raw(0),WalkDepth::Unbounded);
+    assert_eq!(WalkDepth::from_raw(-1),WalkDepth::Unbounded);
+    assert_eq!(WalkDepth::from_raw(3),WalkDepth::Limited(3));
+
+    let mut builder = CGroupWalkBuilder::new("cpu","/");
+    builder.set_order(WalkOrder::Post).set_depth(WalkDepth::Limited(1));
+    assert_eq!(builder.get_order(),WalkOrder::Post);
+    assert_eq!(builder.get_depth(),WalkDepth::Limited(1));
+}
+
+
+#[test]
+fn test_walk_post_order()->Result<(),Box<dyn std::error::Error>>{
+    CGroupInitializer::init()?;
 
+    let pre = CGroupWalkBuilder::new("cpu","/").build()?.groups()?;
+    let post = CGroupWalkBuilder::new("cpu","/")
+        .set_order(WalkOrder::Post)
+        .build()?
+        .groups()?;
+    // the root comes first in pre-order and last in post-order
+    assert_eq!(pre.first(),post.last());
+    assert_eq!(pre.len(),post.len());
+    Ok(())
+}
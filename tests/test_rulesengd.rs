@@ -0,0 +1,98 @@
+use libcgroup_rs::cgrules::{CGRules, ProcessInfo};
+use libcgroup_rs::rulesengd::*;
+use libcgroup_rs::tasks::UnchangeFlags;
+
+
+fn nl_message(what:u32,data:&[u32])->Vec<u8>{
+    let mut payload:Vec<u8> = Vec::new();
+    // cn_msg
+    payload.extend_from_slice(&[0u8;20]);
+    // proc_event header
+    payload.extend_from_slice(&what.to_ne_bytes());
+    payload.extend_from_slice(&0u32.to_ne_bytes());
+    payload.extend_from_slice(&0u64.to_ne_bytes());
+    for field in data {
+        payload.extend_from_slice(&field.to_ne_bytes());
+    }
+    let mut msg:Vec<u8> = Vec::new();
+    msg.extend_from_slice(&((16 + payload.len()) as u32).to_ne_bytes());
+    msg.extend_from_slice(&[0u8;12]);
+    msg.extend(payload);
+    msg
+}
+
+
+#[test]
+fn test_parse_events(){
+    let mut buf = nl_message(0x2,&[100,100]);
+    buf.extend(nl_message(0x1,&[1,1,200,200]));
+    buf.extend(nl_message(0x4,&[300,300,1000,0]));
+
+    let events = ProcConnector::parse_messages(&buf);
+    assert_eq!(events,vec![
+        ProcEvent::Exec{pid:100,tgid:100},
+        ProcEvent::Fork{parent_pid:1,parent_tgid:1,child_pid:200,child_tgid:200},
+        ProcEvent::Uid{pid:300,tgid:300,ruid:1000,euid:0},
+    ]);
+    assert_eq!(events[1].pid(),Some(200));
+    assert_eq!(ProcEvent::parse(&[0u8;8]),None);
+}
+
+
+#[test]
+fn test_plan()->Result<(),Box<dyn std::error::Error>>{
+    let rules = CGRules::parse("
+        nobody      cpu,memory  users/%u/
+        %           freezer     frozen/
+        *           *           default/
+    ")?;
+    let engine = RulesEngine::new(rules)?;
+
+    let nobody = ProcessInfo{
+        pid:42,
+        uid:65534,
+        gid:65534,
+        user:Some(String::from("nobody")),
+        ..Default::default()
+    };
+    assert_eq!(engine.plan(&nobody),vec![
+        (String::from("users/nobody/"),vec![String::from("cpu"),String::from("memory")]),
+        (String::from("frozen/"),vec![String::from("freezer")]),
+    ]);
+
+    let other = ProcessInfo{ pid:43, ..Default::default() };
+    assert_eq!(engine.plan(&other),vec![(String::from("default/"),Vec::new())]);
+
+    // "*" sorts before the controllers and is not narrowed by them
+    let rules = CGRules::parse("
+        nobody      *           shared/
+        %           cpu         shared/
+        %           memory      memory/
+    ")?;
+    let engine = RulesEngine::new(rules)?;
+    assert_eq!(engine.plan(&nobody),vec![
+        (String::from("shared/"),Vec::new()),
+        (String::from("memory/"),vec![String::from("memory")]),
+    ]);
+    Ok(())
+}
+
+
+#[test]
+fn test_unchanged()->Result<(),Box<dyn std::error::Error>>{
+    let mut engine = RulesEngine::new(CGRules::default())?;
+    engine.add_unchanged(10,UnchangeFlags::CHILDREN);
+    engine.add_unchanged(20,UnchangeFlags::NONE);
+
+    engine.handle(&ProcEvent::Fork{parent_pid:10,parent_tgid:10,child_pid:11,child_tgid:11})?;
+    engine.handle(&ProcEvent::Fork{parent_pid:20,parent_tgid:20,child_pid:21,child_tgid:21})?;
+    assert!(engine.is_unchanged(11));
+    assert!(!engine.is_unchanged(21));
+
+    engine.handle(&ProcEvent::Exit{pid:11,tgid:11,exit_code:0})?;
+    engine.add_unchanged(10,UnchangeFlags::CANCEL);
+    assert!(!engine.is_unchanged(11));
+    assert!(!engine.is_unchanged(10));
+    assert!(engine.is_unchanged(20));
+    Ok(())
+}
@@ -1,5 +1,6 @@
 use libcgroup_rs::initialization::CGroupInitializer;
-use libcgroup_rs::manipulation::CGroupBuilder;
+use libcgroup_rs::manipulation::{CGroupBuilder, DeletePolicy, Permissions};
+use libcgroup_rs::tasks::{CGroupTask,UnchangeFlags};
 
 #[test]
 fn test_task_shell()->Result<(),Box<dyn std::error::Error>>{
@@ -11,4 +12,107 @@ fn test_task_shell()->Result<(),Box<dyn std::error::Error>>{
     cg.attach_task_shell()?;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_change_path()->Result<(),Box<dyn std::error::Error>>{
+    CGroupInitializer::init()?;
+
+    let pid = std::process::id() as i32;
+    CGroupTask::change_path("/foo",pid,&["cpu"])?;
+    println!("Current = {}",CGroupTask::current_path(pid,"cpu")?);
+
+    Ok(())
+}
+
+
+#[test]
+fn test_unchanged_flags(){
+    let flags = UnchangeFlags::CHILDREN | UnchangeFlags::CANCEL;
+    assert_eq!(flags.bits(),0x3);
+    assert!(flags.contains(UnchangeFlags::CHILDREN));
+    assert!(!UnchangeFlags::NONE.contains(UnchangeFlags::CANCEL));
+}
+
+
+#[test]
+fn test_clone_keeps_settings()->Result<(),Box<dyn std::error::Error>>{
+    let cg = CGroupBuilder::new("foo_clone")?;
+    cg.set_sticky(Some(UnchangeFlags::CHILDREN));
+    cg.set_permissions(Permissions::new(0o755,0o644,0o664));
+
+    let clone = cg.clone();
+    assert_eq!(clone.get_sticky(),Some(UnchangeFlags::CHILDREN));
+    assert_eq!(clone.get_permissions(),Some(Permissions::new(0o755,0o644,0o664)));
+    clone.free();
+    cg.free();
+    Ok(())
+}
+
+
+#[test]
+fn test_kill()->Result<(),Box<dyn std::error::Error>>{
+    CGroupInitializer::init()?;
+
+    let cg = CGroupBuilder::new("foo_kill")?;
+    cg.add_controller("cpu")?;
+    cg.create(0)?;
+
+    let mut child = std::process::Command::new("sleep").arg("60").spawn()?;
+    cg.attach_task_pid(child.id() as i32)?;
+
+    let remaining = cg.kill(libc::SIGTERM)?;
+    assert!(remaining.is_empty());
+    child.wait()?;
+
+    cg.delete(0)?;
+    Ok(())
+}
+
+
+#[test]
+fn test_kill_root()->Result<(),Box<dyn std::error::Error>>{
+    for name in ["/",""].iter() {
+        let cg = CGroupBuilder::new(*name)?;
+        cg.add_controller("cpu")?;
+        let e = cg.kill(libc::SIGTERM).unwrap_err();
+        assert_eq!(e.kind(),std::io::ErrorKind::InvalidInput);
+        cg.free();
+    }
+    Ok(())
+}
+
+
+#[test]
+fn test_delete_tree()->Result<(),Box<dyn std::error::Error>>{
+    CGroupInitializer::init()?;
+
+    let root = CGroupBuilder::new("foo_tree")?;
+    root.add_controller("cpu")?;
+    root.create(0)?;
+    let child = CGroupBuilder::new("foo_tree/a/b")?;
+    child.add_controller("cpu")?;
+    child.create(0)?;
+    assert_eq!(child.get_controller_names(),vec!["cpu"]);
+
+    let mut sleeper = std::process::Command::new("sleep").arg("60").spawn()?;
+    child.attach_task_pid(sleeper.id() as i32)?;
+
+    assert!(root.delete_tree(DeletePolicy::Fail).is_err());
+    root.delete_tree(DeletePolicy::Kill)?;
+    sleeper.wait()?;
+    Ok(())
+}
+
+
+#[test]
+fn test_delete_tree_root()->Result<(),Box<dyn std::error::Error>>{
+    let cg = CGroupBuilder::new("/")?;
+    cg.add_controller("cpu")?;
+    for policy in [DeletePolicy::Kill,DeletePolicy::MoveToParent,DeletePolicy::Fail].iter() {
+        let e = cg.delete_tree(*policy).unwrap_err();
+        assert_eq!(e.kind(),std::io::ErrorKind::InvalidInput);
+    }
+    cg.free();
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use libcgroup_rs::initialization::CGroupInitializer;
+use libcgroup_rs::manipulation::CGroupBuilder;
+use libcgroup_rs::templates::{Templates, TemplateSubst, CGFlags};
+use libcgroup_rs::error::{cg_get_error, C_EC_GROUP_NOT_ALLOWED};
+
+#[test]
+fn test_substitute(){
+    let subst = TemplateSubst{
+        user:Some(String::from("alice")),
+        uid:1000,
+        group:None,
+        gid:100,
+        procname:Some(String::from("nginx")),
+        pid:42,
+    };
+    assert_eq!(subst.substitute("users/%u"),"users/alice");
+    assert_eq!(subst.substitute("users/%U/%g/%G"),"users/1000/100/100");
+    assert_eq!(subst.substitute("jobs/%p.%P"),"jobs/nginx.42");
+    assert_eq!(subst.substitute("odd/%x/100%"),"odd/%x/100%");
+
+    let flags = CGFlags::USE_CACHE | CGFlags::USE_TEMPLATE_CACHE;
+    assert_eq!(flags.bits(),0x03);
+}
+
+
+#[test]
+fn test_subst_for_pid()->Result<(),Box<dyn std::error::Error>>{
+    let pid = std::process::id() as i32;
+    let subst = TemplateSubst::for_pid(pid)?;
+    println!("Subst = {:?}",subst);
+    assert_eq!(subst.pid,pid);
+    assert_eq!(subst.uid,unsafe { libc::geteuid() });
+    if subst.uid == 0 {
+        assert_eq!(subst.user.as_deref(),Some("root"));
+    }
+    assert_eq!(subst.substitute("%P"),pid.to_string());
+    Ok(())
+}
+
+
+#[test]
+fn test_instantiate()->Result<(),Box<dyn std::error::Error>>{
+    CGroupInitializer::init()?;
+
+    let pathname = std::env::temp_dir().join("libcgroup_rs_templates.conf");
+    std::fs::write(&pathname,"template libcgroup_rs_tpl_%P {\n    cpu {\n    }\n}\n")?;
+    Templates::init(&pathname.to_string_lossy())?;
+
+    let subst = TemplateSubst::for_pid(std::process::id() as i32)?;
+    let cg = match Templates::instantiate("libcgroup_rs_tpl_%P",&subst,&["cpu"],CGFlags::USE_TEMPLATE_CACHE) {
+        Ok(cg) => cg,
+        //only root
+        Err(e) if e.kind().eq(&cg_get_error(C_EC_GROUP_NOT_ALLOWED).kind()) => return Ok(()),
+        Err(e) => return Err(Box::new(e)),
+    };
+    assert_eq!(cg.get_name(),format!("libcgroup_rs_tpl_{}",std::process::id()));
+    assert_eq!(cg.get_controller_names(),vec![String::from("cpu")]);
+
+    // the group exists in the hierarchy
+    let created = CGroupBuilder::new(cg.get_name())?;
+    created.load()?;
+    assert!(created.get_controller_names().contains(&String::from("cpu")));
+
+    created.delete(0)?;
+    created.free();
+    cg.free();
+    std::fs::remove_file(pathname)?;
+    Ok(())
+}
+
+
+#[test]
+fn test_instantiate_nul(){
+    let subst = TemplateSubst::for_pid(std::process::id() as i32).unwrap();
+    let e = Templates::instantiate("libcgroup_rs\0tpl",&subst,&["cpu"],CGFlags::USE_TEMPLATE_CACHE).unwrap_err();
+    assert_eq!(e.kind(),std::io::ErrorKind::InvalidInput);
+}
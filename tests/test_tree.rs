@@ -0,0 +1,79 @@
+use libcgroup_rs::initialization::CGroupInitializer;
+use libcgroup_rs::iterators::{WalkEntry, WalkEntryKind};
+use libcgroup_rs::tree::CGroupTree;
+use std::path::{Path, PathBuf};
+
+
+fn entry(kind:WalkEntryKind,full_path:PathBuf)->WalkEntry{
+    WalkEntry{
+        kind,
+        path:PathBuf::from(full_path.file_name().unwrap()),
+        parent:full_path.parent().map(|p| PathBuf::from(p.file_name().unwrap())).unwrap_or_default(),
+        full_path,
+        depth:0,
+        base_level:0,
+    }
+}
+
+/// mount/{a/{x,y},b} with cgroup.procs and cpu.shares files, entries in pre-order
+fn hierarchy(mount:&Path)->Result<Vec<WalkEntry>,std::io::Error>{
+    let mut entries = Vec::new();
+    for (dir,procs) in [("",""),("a","1\n2\n"),("a/x","3\n"),("a/y",""),("b","4\n")].iter() {
+        let full_path = mount.join(dir);
+        std::fs::create_dir_all(&full_path)?;
+        std::fs::write(full_path.join("cgroup.procs"),procs)?;
+        std::fs::write(full_path.join("cpu.shares"),"1024\n")?;
+        entries.push(entry(WalkEntryKind::Dir,full_path.clone()));
+        entries.push(entry(WalkEntryKind::File,full_path.join("cgroup.procs")));
+        entries.push(entry(WalkEntryKind::File,full_path.join("cpu.shares")));
+    }
+    Ok(entries)
+}
+
+
+#[test]
+fn test_tree_entries()->Result<(),Box<dyn std::error::Error>>{
+    let mount = std::env::temp_dir().join(format!("cg_tree_{}",std::process::id()));
+    let mut entries = hierarchy(&mount)?;
+    // files of a group walked after its children
+    std::fs::write(mount.join("a/cpu.cfs_quota_us"),"-1\n")?;
+    std::fs::write(mount.join("cpu.cfs_period_us"),"100000\n")?;
+    entries.insert(12,entry(WalkEntryKind::File,mount.join("a/cpu.cfs_quota_us")));
+    entries.push(entry(WalkEntryKind::File,mount.join("cpu.cfs_period_us")));
+    let tree = CGroupTree::from_entries("cpu",&mount.to_string_lossy(),entries,true)?;
+    std::fs::remove_dir_all(&mount)?;
+
+    assert_eq!(tree.root.path,"/");
+    let paths:Vec<&str> = tree.iter().map(|node| node.path.as_str()).collect();
+    assert_eq!(paths,vec!["/","/a","/a/x","/a/y","/b"]);
+
+    let a = tree.find("/a").unwrap();
+    assert_eq!(a.name,"a");
+    assert_eq!(a.depth,1);
+    assert_eq!(a.procs,2);
+    assert_eq!(a.total_procs(),3);
+    assert_eq!(a.values.get("cpu.shares").map(|v| v.as_str()),Some("1024"));
+    assert!(!a.values.contains_key("cgroup.procs"));
+    assert_eq!(a.values.get("cpu.cfs_quota_us").map(|v| v.as_str()),Some("-1"));
+    assert_eq!(tree.root.values.get("cpu.cfs_period_us").map(|v| v.as_str()),Some("100000"));
+    assert!(!tree.find("/a/y").unwrap().values.contains_key("cpu.cfs_quota_us"));
+    assert_eq!(a.descendants().len(),2);
+
+    assert!(tree.find("a/y/").unwrap().is_leaf());
+    assert!(tree.find("/c").is_none());
+    assert_eq!(tree.iter_depth(1).count(),3);
+    assert_eq!(tree.root.total_procs(),4);
+    Ok(())
+}
+
+
+#[test]
+fn test_tree()->Result<(),Box<dyn std::error::Error>>{
+    CGroupInitializer::init()?;
+
+    let tree = CGroupTree::from("cpu","/")?;
+    for node in tree.iter_depth(2) {
+        println!("{}{} = {}","  ".repeat(node.depth),node.path,node.procs);
+    }
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use libcgroup_rs::initialization::CGroupInitializer;
 #[allow(unused_imports)]
 use libcgroup_rs::manipulation::CGroupBuilder;
+use libcgroup_rs::manipulation::{Permissions, NO_PERMS};
 use libcgroup_rs::extend::MountBuilder;
 #[allow(unused_imports)]
 use libcgroup_rs::error::{cg_get_error, C_EC_GROUP_NOT_MOUNTED, C_EC_GROUP_NOT_ALLOWED, cg_get_last_errno, cg_get_last_error_str};
@@ -25,7 +26,7 @@ fn test_initialize()->Result<(),Box<dyn std::error::Error>>{
                 .set_type_name("cgroup")
                 .set_opts("cpu");
 
-            if !space.exists() {
+            if !space.is_mounted()? {
                 space.mount()?;
             }
             CGroupInitializer::init()?;
@@ -100,4 +101,42 @@ fn test_create_container()->Result<(),Box<dyn std::error::Error>>{
     container.create(0)?;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_permissions(){
+    let perms = Permissions::new(0o750,0o640,0o660);
+    assert_eq!(perms.to_raw(),(0o750,0o640,0o660));
+
+    // unset modes keep the system default
+    assert_eq!(Permissions::default().to_raw(),(NO_PERMS,NO_PERMS,NO_PERMS));
+    let partial = Permissions{ dir_mode:Some(0o700), ..Default::default() };
+    assert_eq!(partial.to_raw(),(0o700,NO_PERMS,NO_PERMS));
+}
+
+
+#[test]
+fn test_create_permissions()->Result<(),Box<dyn std::error::Error>>{
+    use std::os::unix::fs::PermissionsExt;
+
+    CGroupInitializer::init()?;
+
+    let cg = CGroupBuilder::new("foo_perms")?;
+    cg.add_controller("cpu")?;
+    cg.set_permissions(Permissions::new(0o750,0o640,0o660));
+    assert_eq!(cg.get_permissions(),Some(Permissions::new(0o750,0o640,0o660)));
+
+    //only root
+    match cg.create(0) {
+        Ok(_) => (),
+        Err(e) if e.kind().eq(&cg_get_error(C_EC_GROUP_NOT_ALLOWED).kind()) => return Ok(()),
+        Err(e) => return Err(Box::new(e)),
+    }
+
+    let mount_point = CGroupInitializer::get_subsys_mount_point("cpu").unwrap();
+    let dir = std::path::Path::new(&mount_point).join("foo_perms");
+    assert_eq!(std::fs::metadata(&dir)?.permissions().mode() & 0o777,0o750);
+
+    cg.delete(0)?;
+    Ok(())
+}